@@ -0,0 +1,308 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+/// Boolean logic on one row per instruction:
+///
+///   a | b | out | q_bool | q_and | q_or | q_xor | q_not | q_nand
+///
+/// Every gate also checks `a·(1−a) = 0` and, for binary instructions,
+/// `b·(1−b) = 0`, so the operands need not have been constrained before.
+#[derive(Clone, Debug)]
+pub struct BooleanConfig {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub out: Column<Advice>,
+    pub q_bool: Selector,
+    pub q_and: Selector,
+    pub q_or: Selector,
+    pub q_xor: Selector,
+    pub q_not: Selector,
+    pub q_nand: Selector,
+}
+
+pub struct BooleanChip<F: FieldExt> {
+    config: BooleanConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> BooleanChip<F> {
+    pub fn construct(config: BooleanConfig) -> Self {
+        BooleanChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 3]) -> BooleanConfig {
+        let [a, b, out] = advice;
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+
+        let q_bool = meta.selector();
+        let q_and = meta.selector();
+        let q_or = meta.selector();
+        let q_xor = meta.selector();
+        let q_not = meta.selector();
+        let q_nand = meta.selector();
+
+        let bool_check = |x: Expression<F>| x.clone() * (Expression::Constant(F::one()) - x);
+
+        meta.create_gate("bool", |meta| {
+            let q_bool = meta.query_selector(q_bool);
+            let a = meta.query_advice(a, Rotation::cur());
+
+            Constraints::with_selector(q_bool, [("a·(1−a)", bool_check(a))])
+        });
+
+        meta.create_gate("not", |meta| {
+            let q_not = meta.query_selector(q_not);
+            let a = meta.query_advice(a, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+
+            Constraints::with_selector(
+                q_not,
+                [
+                    ("a·(1−a)", bool_check(a.clone())),
+                    ("not", out - (Expression::Constant(F::one()) - a)),
+                ],
+            )
+        });
+
+        // out = f(a, b) for the binary instructions
+        #[allow(clippy::type_complexity)]
+        let binary: [(
+            &'static str,
+            Selector,
+            fn(Expression<F>, Expression<F>) -> Expression<F>,
+        ); 4] = [
+            ("and", q_and, |a, b| a * b),
+            ("or", q_or, |a, b| a.clone() + b.clone() - a * b),
+            ("xor", q_xor, |a, b| {
+                a.clone() + b.clone() - Expression::Constant(F::from(2)) * a * b
+            }),
+            ("nand", q_nand, |a, b| {
+                Expression::Constant(F::one()) - a * b
+            }),
+        ];
+        for (name, selector, f) in binary {
+            meta.create_gate(name, |meta| {
+                let q = meta.query_selector(selector);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let out = meta.query_advice(out, Rotation::cur());
+
+                Constraints::with_selector(
+                    q,
+                    [
+                        ("a·(1−a)", bool_check(a.clone())),
+                        ("b·(1−b)", bool_check(b.clone())),
+                        (name, out - f(a, b)),
+                    ],
+                )
+            });
+        }
+
+        BooleanConfig {
+            a,
+            b,
+            out,
+            q_bool,
+            q_and,
+            q_or,
+            q_xor,
+            q_not,
+            q_nand,
+        }
+    }
+
+    /// Assigns a private value constrained to `{0, 1}`.
+    pub fn assign_bool(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "bool",
+            |mut region| {
+                self.config.q_bool.enable(&mut region, 0)?;
+                region.assign_advice(|| "b", self.config.a, 0, || value)
+            },
+        )
+    }
+
+    /// Constrains an existing cell to `{0, 1}` and returns its copy.
+    pub fn constrain_bool(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "bool",
+            |mut region| {
+                self.config.q_bool.enable(&mut region, 0)?;
+                cell.copy_advice(|| "b", &mut region, self.config.a, 0)
+            },
+        )
+    }
+
+    pub fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.binary(layouter, "and", self.config.q_and, a, b, |a, b| a * b)
+    }
+
+    pub fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.binary(layouter, "or", self.config.q_or, a, b, |a, b| a + b - a * b)
+    }
+
+    pub fn xor(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.binary(layouter, "xor", self.config.q_xor, a, b, |a, b| {
+            a + b - F::from(2) * a * b
+        })
+    }
+
+    pub fn nand(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.binary(layouter, "nand", self.config.q_nand, a, b, |a, b| {
+            F::one() - a * b
+        })
+    }
+
+    pub fn not(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "not",
+            |mut region| {
+                self.config.q_not.enable(&mut region, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, self.config.a, 0)?;
+                let out = a.value().map(|a| F::one() - a);
+                region.assign_advice(|| "out", self.config.out, 0, || out)
+            },
+        )
+    }
+
+    fn binary(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        selector: Selector,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        f: impl Fn(F, F) -> F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, self.config.a, 0)?;
+                let b = b.copy_advice(|| "b", &mut region, self.config.b, 0)?;
+                let out = a.value().zip(b.value()).map(|(a, b)| f(*a, *b));
+                region.assign_advice(|| "out", self.config.out, 0, || out)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// Exposes `[a and b, a or b, a xor b, not a, a nand b]` for boolean `a`, `b`.
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        a: Value<F>,
+        b: Value<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+        type Config = (BooleanConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            (BooleanChip::configure(meta, advice), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = BooleanChip::construct(config);
+
+            let a = chip.assign_bool(layouter.namespace(|| "a"), self.a)?;
+            let b = chip.assign_bool(layouter.namespace(|| "b"), self.b)?;
+
+            let outputs = [
+                chip.and(layouter.namespace(|| "and"), &a, &b)?,
+                chip.or(layouter.namespace(|| "or"), &a, &b)?,
+                chip.xor(layouter.namespace(|| "xor"), &a, &b)?,
+                chip.not(layouter.namespace(|| "not"), &a)?,
+                chip.nand(layouter.namespace(|| "nand"), &a, &b)?,
+            ];
+            for (row, out) in outputs.iter().enumerate() {
+                layouter.constrain_instance(out.cell(), instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn truth_tables() {
+        for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+            let circuit = MyCircuit {
+                a: Value::known(Fp::from(a as u64)),
+                b: Value::known(Fp::from(b as u64)),
+            };
+            let public_inputs = [a & b, a | b, a ^ b, !a, !(a & b)]
+                .map(|x| Fp::from(x as u64))
+                .to_vec();
+
+            let prover = MockProver::run(4, &circuit, vec![public_inputs]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn non_boolean_fails() {
+        // 2 and 1 = 2, 2 or 1 = 1, 2 xor 1 = -1, not 2 = -1, 2 nand 1 = -1
+        let circuit = MyCircuit {
+            a: Value::known(Fp::from(2)),
+            b: Value::known(Fp::one()),
+        };
+        let public_inputs = vec![Fp::from(2), Fp::one(), -Fp::one(), -Fp::one(), -Fp::one()];
+
+        let prover = MockProver::run(4, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+use crate::is_zero::{IsZeroChip, IsZeroConfig};
+
+/// `1/value` with a zero-divisor flag, on top of the is-zero gadget: the
+/// inverse is its `value_inv` witness and the flag must equal its output.
+///
+///   value | flag | value_inv
+///     a   |  0   |    1/a
+///     0   |  1   |     0
+///
+/// `value · value_inv = 1 − flag` holds through the is-zero gate, and
+/// `flag · value_inv = 0` pins the inverse of a zero value to 0. The flag and
+/// the inverse share a row, `value` may sit anywhere the caller queries it.
+#[derive(Clone, Debug)]
+pub struct InvConfig<F> {
+    pub flag: Column<Advice>,
+    pub is_zero: IsZeroConfig<F>,
+}
+
+pub struct InvChip<F: FieldExt> {
+    config: InvConfig<F>,
+}
+
+impl<F: FieldExt> InvChip<F> {
+    pub fn construct(config: InvConfig<F>) -> Self {
+        InvChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        flag: Column<Advice>,
+        value_inv: Column<Advice>,
+    ) -> InvConfig<F> {
+        let is_zero = IsZeroChip::configure(meta, &q_enable, value, value_inv);
+
+        meta.create_gate("inv", |meta| {
+            let q_enable = q_enable(meta);
+            let flag = meta.query_advice(flag, Rotation::cur());
+            let value_inv = meta.query_advice(value_inv, Rotation::cur());
+
+            vec![
+                q_enable.clone() * (flag.clone() - is_zero.expr()),
+                q_enable * flag * value_inv,
+            ]
+        });
+
+        InvConfig { flag, is_zero }
+    }
+
+    /// Assigns the inverse of `value` and its flag at `offset`, returns both
+    /// in this order. The caller enables the gate and lays out `value`.
+    #[allow(clippy::type_complexity)]
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let flag = value.map(|value| F::from(bool::from(value.is_zero())));
+        let flag = region.assign_advice(|| "flag", self.config.flag, offset, || flag)?;
+
+        let is_zero_chip = IsZeroChip::construct(self.config.is_zero.clone());
+        let inv = is_zero_chip.assign(region, offset, value)?;
+
+        Ok((inv, flag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{arithmetic::Field, dev::MockProver, pasta::Fp};
+
+    /// Exposes `[1/a, flag]`, with `flag` forced to `forged_flag` when set.
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        a: Value<F>,
+        forged_flag: Option<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+        type Config = (InvConfig<F>, Column<Advice>, Selector, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let [value, flag, value_inv] = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            let q_inv = meta.selector();
+            meta.enable_equality(flag);
+            meta.enable_equality(value_inv);
+            meta.enable_equality(instance);
+
+            let config = InvChip::configure(
+                meta,
+                |meta| meta.query_selector(q_inv),
+                |meta| meta.query_advice(value, Rotation::cur()),
+                flag,
+                value_inv,
+            );
+            (config, value, q_inv, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, value, q_inv, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let flag_column = config.flag;
+            let chip = InvChip::construct(config);
+
+            let (inv, flag) = layouter.assign_region(
+                || "inv",
+                |mut region| {
+                    q_inv.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", value, 0, || self.a)?;
+                    let (inv, flag) = chip.assign(&mut region, 0, self.a)?;
+
+                    let flag = match self.forged_flag {
+                        Some(forged) => region.assign_advice(
+                            || "forged flag",
+                            flag_column,
+                            0,
+                            || Value::known(forged),
+                        )?,
+                        None => flag,
+                    };
+                    Ok((inv, flag))
+                },
+            )?;
+
+            layouter.constrain_instance(inv.cell(), instance, 0)?;
+            layouter.constrain_instance(flag.cell(), instance, 1)
+        }
+    }
+
+    #[test]
+    fn inv_and_flag() {
+        let a = Fp::from(4);
+        let circuit = MyCircuit {
+            a: Value::known(a),
+            forged_flag: None,
+        };
+        let public_inputs = vec![a.invert().unwrap(), Fp::zero()];
+        let prover = MockProver::run(4, &circuit, vec![public_inputs]).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = MyCircuit {
+            a: Value::known(Fp::zero()),
+            forged_flag: None,
+        };
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::zero(), Fp::one()]]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn forged_flag_fails() {
+        // a zero value claimed invertible, and a non-zero one claimed zero
+        for (a, forged, inv) in [
+            (Fp::zero(), Fp::zero(), Fp::zero()),
+            (Fp::from(4), Fp::one(), Fp::from(4).invert().unwrap()),
+        ] {
+            let circuit = MyCircuit {
+                a: Value::known(a),
+                forged_flag: Some(forged),
+            };
+            let prover = MockProver::run(4, &circuit, vec![vec![inv, forged]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+}
@@ -57,10 +57,9 @@ impl<F: FieldExt> IsZeroChip<F> {
         region: &mut Region<'_, F>,
         offset: usize,
         value: Value<F>,
-    ) -> Result<(), Error> {
+    ) -> Result<AssignedCell<F, F>, Error> {
         let value_inv = value.map(|value| value.invert().unwrap_or(F::zero()));
-        region.assign_advice(|| "value inv", self.config.value_inv, offset, || value_inv)?;
-        Ok(())
+        region.assign_advice(|| "value inv", self.config.value_inv, offset, || value_inv)
     }
 }
 
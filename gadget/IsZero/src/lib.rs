@@ -1 +1,4 @@
+pub mod boolean;
+pub mod inv;
 pub mod is_zero;
+pub mod select;
@@ -0,0 +1,291 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+/// `cond ? x : y` on one row, and an n-way mux over a one-hot prefix sum.
+///
+///   select:  cond | x   | y     | out      with  out = y + cond·(x − y)
+///
+///   mux:     s    | x   | idx   | acc
+///            0    |     | 0     | 0        q_mux_init
+///            s_1  | x_0 | idx_1 | acc_1    q_mux
+///            ...
+///            1    | x_{n−1} | index | out  q_mux, q_mux_last
+///
+/// where `s` is a boolean, non-decreasing prefix sum of the one-hot vector
+/// `e_i = s_i − s_{i−1}`, `idx` counts the rows before the hot one and `acc`
+/// sums `e_i·x_i`.
+#[derive(Clone, Debug)]
+pub struct SelectConfig {
+    pub advice: [Column<Advice>; 4],
+    pub q_select: Selector,
+    pub q_mux: Selector,
+    pub q_mux_init: Selector,
+    pub q_mux_last: Selector,
+}
+
+pub struct SelectChip<F: FieldExt> {
+    config: SelectConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> SelectChip<F> {
+    pub fn construct(config: SelectConfig) -> Self {
+        SelectChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 4]) -> SelectConfig {
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+
+        let q_select = meta.selector();
+        let q_mux = meta.selector();
+        let q_mux_init = meta.selector();
+        let q_mux_last = meta.selector();
+
+        let one = || Expression::Constant(F::one());
+
+        meta.create_gate("select", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let [cond, x, y, out] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+
+            Constraints::with_selector(
+                q_select,
+                [
+                    ("cond·(1−cond)", cond.clone() * (one() - cond.clone())),
+                    ("select", out - y.clone() - cond * (x - y)),
+                ],
+            )
+        });
+
+        meta.create_gate("mux", |meta| {
+            let q_mux = meta.query_selector(q_mux);
+            let [s_col, x_col, idx_col, acc_col] = advice;
+            let [s_prev, idx_prev, acc_prev] =
+                [s_col, idx_col, acc_col].map(|column| meta.query_advice(column, Rotation::prev()));
+            let [s, x, idx, acc] = [s_col, x_col, idx_col, acc_col]
+                .map(|column| meta.query_advice(column, Rotation::cur()));
+            let e = s.clone() - s_prev;
+
+            Constraints::with_selector(
+                q_mux,
+                [
+                    ("s·(1−s)", s.clone() * (one() - s.clone())),
+                    ("e·(1−e)", e.clone() * (one() - e.clone())),
+                    ("idx", idx - idx_prev - (one() - s)),
+                    ("acc", acc - acc_prev - e * x),
+                ],
+            )
+        });
+
+        meta.create_gate("mux init", |meta| {
+            let q_mux_init = meta.query_selector(q_mux_init);
+            let [s, idx, acc] = [advice[0], advice[2], advice[3]]
+                .map(|column| meta.query_advice(column, Rotation::cur()));
+
+            Constraints::with_selector(q_mux_init, [("s", s), ("idx", idx), ("acc", acc)])
+        });
+
+        meta.create_gate("mux last", |meta| {
+            let q_mux_last = meta.query_selector(q_mux_last);
+            let s = meta.query_advice(advice[0], Rotation::cur());
+
+            Constraints::with_selector(q_mux_last, [("s", s - one())])
+        });
+
+        SelectConfig {
+            advice,
+            q_select,
+            q_mux,
+            q_mux_init,
+            q_mux_last,
+        }
+    }
+
+    /// Returns `cond ? x : y`, `cond` is constrained to `{0, 1}`.
+    pub fn select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cond: &AssignedCell<F, F>,
+        x: &AssignedCell<F, F>,
+        y: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [cond_col, x_col, y_col, out_col] = self.config.advice;
+
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                self.config.q_select.enable(&mut region, 0)?;
+
+                let cond = cond.copy_advice(|| "cond", &mut region, cond_col, 0)?;
+                let x = x.copy_advice(|| "x", &mut region, x_col, 0)?;
+                let y = y.copy_advice(|| "y", &mut region, y_col, 0)?;
+
+                let out = cond
+                    .value()
+                    .zip(x.value())
+                    .zip(y.value())
+                    .map(|((cond, x), y)| if *cond == F::zero() { *y } else { *x });
+                region.assign_advice(|| "out", out_col, 0, || out)
+            },
+        )
+    }
+
+    /// Returns `xs[index]`. An `index` outside of `0..xs.len()` is a
+    /// `Error::Synthesis` when known, and unsatisfiable otherwise.
+    pub fn mux(
+        &self,
+        mut layouter: impl Layouter<F>,
+        index: &AssignedCell<F, F>,
+        xs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [s_col, x_col, idx_col, acc_col] = self.config.advice;
+
+        let hot = index
+            .value()
+            .map(|index| (0..xs.len()).position(|i| F::from(i as u64) == *index));
+        hot.error_if_known_and(|hot| hot.is_none())?;
+        let hot = hot.map(|hot| hot.unwrap_or(0));
+
+        layouter.assign_region(
+            || "mux",
+            |mut region| {
+                self.config.q_mux_init.enable(&mut region, 0)?;
+                for column in [s_col, idx_col, acc_col] {
+                    region.assign_advice(|| "init", column, 0, || Value::known(F::zero()))?;
+                }
+
+                let (mut idx, mut acc) = (Value::known(F::zero()), Value::known(F::zero()));
+                let mut out = None;
+                for (i, x) in xs.iter().enumerate() {
+                    let row = i + 1;
+                    self.config.q_mux.enable(&mut region, row)?;
+
+                    let x = x.copy_advice(|| "x", &mut region, x_col, row)?;
+                    let s = hot.map(|hot| F::from((i >= hot) as u64));
+                    let e = hot.map(|hot| F::from((i == hot) as u64));
+
+                    idx = idx + s.map(|s| F::one() - s);
+                    acc = acc + e * x.value();
+
+                    region.assign_advice(|| "s", s_col, row, || s)?;
+                    let idx_cell = region.assign_advice(|| "idx", idx_col, row, || idx)?;
+                    out = Some((
+                        idx_cell,
+                        region.assign_advice(|| "acc", acc_col, row, || acc)?,
+                    ));
+                }
+
+                let (idx_cell, out) = out.ok_or(Error::Synthesis)?;
+                self.config.q_mux_last.enable(&mut region, xs.len())?;
+                region.constrain_equal(idx_cell.cell(), index.cell())?;
+
+                Ok(out)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// Exposes `[cond ? x_0 : x_1, xs[index]]`.
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        cond: Value<F>,
+        index: Value<F>,
+        xs: Vec<Value<F>>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+        type Config = (SelectConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                xs: vec![Value::unknown(); self.xs.len()],
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            (SelectChip::configure(meta, advice), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let column = config.advice[0];
+            let chip = SelectChip::construct(config);
+
+            let (cond, index, xs) = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let cond = region.assign_advice(|| "cond", column, 0, || self.cond)?;
+                    let index = region.assign_advice(|| "index", column, 1, || self.index)?;
+                    let xs = self
+                        .xs
+                        .iter()
+                        .enumerate()
+                        .map(|(i, x)| region.assign_advice(|| "x", column, i + 2, || *x))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok((cond, index, xs))
+                },
+            )?;
+
+            let selected = chip.select(layouter.namespace(|| "select"), &cond, &xs[0], &xs[1])?;
+            let muxed = chip.mux(layouter.namespace(|| "mux"), &index, &xs)?;
+
+            layouter.constrain_instance(selected.cell(), instance, 0)?;
+            layouter.constrain_instance(muxed.cell(), instance, 1)
+        }
+    }
+
+    fn circuit(cond: u64, index: u64) -> MyCircuit<Fp> {
+        MyCircuit {
+            cond: Value::known(Fp::from(cond)),
+            index: Value::known(Fp::from(index)),
+            xs: [10, 20, 30, 40, 50]
+                .map(|x| Value::known(Fp::from(x)))
+                .to_vec(),
+        }
+    }
+
+    #[test]
+    fn select_and_mux() {
+        for (cond, selected) in [(1, 10), (0, 20)] {
+            for index in 0..5 {
+                let public_inputs = vec![Fp::from(selected), Fp::from(10 * (index + 1))];
+                let prover =
+                    MockProver::run(5, &circuit(cond, index), vec![public_inputs]).unwrap();
+                prover.assert_satisfied();
+            }
+        }
+
+        // a non-boolean condition
+        let public_inputs = vec![Fp::from(10 * 2 - 20), Fp::from(10)];
+        let prover = MockProver::run(5, &circuit(2, 0), vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn mux_out_of_range() {
+        let public_inputs = vec![Fp::from(10), Fp::zero()];
+        assert!(matches!(
+            MockProver::run(5, &circuit(1, 5), vec![public_inputs]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
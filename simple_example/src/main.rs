@@ -1,7 +1,8 @@
 use std::marker::PhantomData;
 
-use group::ff::Field;
+use gadget::inv::{InvChip, InvConfig};
 use halo2_proofs::{
+    arithmetic::FieldExt as Field,
     circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner, Value},
     plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
     poly::Rotation,
@@ -26,6 +27,21 @@ trait NumericInstructions<F: Field>: Chip<F> {
         b: Self::Num,
     ) -> Result<Self::Num, Error>;
 
+    /// Returns `(1/a, 0)`, or `(0, 1)` when `a` is zero.
+    fn inv(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error>;
+
+    /// Returns `(a/b, 0)`, or `(0, 1)` when `b` is zero.
+    fn div(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error>;
+
     /// Exposes a number as a public input to the circuit.
     fn expose_public(
         &self,
@@ -40,7 +56,7 @@ trait NumericInstructions<F: Field>: Chip<F> {
 /// Chip state is stored in a config struct. This is generated by the chip
 /// during configuration, and then stored inside the chip.
 #[derive(Clone, Debug)]
-struct FieldConfig {
+struct FieldConfig<F: Field> {
     /// For this chip, we will use two advice columns to implement our instructions.
     /// These are also the columns through which we communicate with other parts of
     /// the circuit.
@@ -54,12 +70,18 @@ struct FieldConfig {
     // This is important when building larger circuits, where columns are used by
     // multiple sets of instructions.
     s_mul: Selector,
+
+    // The selector of the inverse gate, used by `NumericInstructions::inv` and `div`.
+    s_inv: Selector,
+
+    // The inverse gate itself, from the is-zero gadget.
+    inv: InvConfig<F>,
 }
 
 /// The chip that will implement our instructions! Chips store their own
 /// config, as well as type markers if necessary.
 struct FieldChip<F: Field> {
-    config: FieldConfig,
+    config: FieldConfig<F>,
     _marker: PhantomData<F>,
 }
 
@@ -83,6 +105,7 @@ impl<F: Field> FieldChip<F> {
             meta.enable_equality(*column);
         }
         let s_mul = meta.selector();
+        let s_inv = meta.selector();
 
         // Define our multiplication gate!
         meta.create_gate("mul", |meta| {
@@ -114,17 +137,37 @@ impl<F: Field> FieldChip<F> {
             vec![s_mul * (lhs * rhs - out)]
         });
 
+        // Our inverse gate comes from the is-zero gadget, with the flag
+        // witnessed in the second column next to the inverse:
+        //
+        // | a0  | a1   | s_inv |
+        // |-----|------|-------|
+        // | a   |      |       |
+        // | inv | flag | s_inv |
+        //
+        // - When a != 0, flag = 0 and inv = 1/a.
+        // - When a = 0, flag = 1 and inv = 0.
+        let inv = InvChip::configure(
+            meta,
+            |meta| meta.query_selector(s_inv),
+            |meta| meta.query_advice(advice[0], Rotation::prev()),
+            advice[1],
+            advice[0],
+        );
+
         FieldConfig {
             advice,
             instance,
             s_mul,
+            s_inv,
+            inv,
         }
     }
 }
 
 // ANCHOR: chip-impl
 impl<F: Field> Chip<F> for FieldChip<F> {
-    type Config = FieldConfig;
+    type Config = FieldConfig<F>;
     type Loaded = ();
 
     fn config(&self) -> &Self::Config {
@@ -215,6 +258,41 @@ impl<F: Field> NumericInstructions<F> for FieldChip<F> {
         )
     }
 
+    fn inv(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "inv",
+            |mut region: Region<'_, F>| {
+                config.s_inv.enable(&mut region, 1)?;
+
+                a.0.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+
+                // A zero `a` has no inverse, it is flagged and gets 0 instead.
+                let inv_chip = InvChip::construct(config.inv.clone());
+                let (inv, flag) = inv_chip.assign(&mut region, 1, a.0.value().copied())?;
+
+                Ok((Number(inv), Number(flag)))
+            },
+        )
+    }
+
+    fn div(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        let (inv, flag) = self.inv(layouter.namespace(|| "1 / b"), b)?;
+        let c = self.mul(layouter.namespace(|| "a * (1 / b)"), a, inv)?;
+
+        Ok((c, flag))
+    }
+
     fn expose_public(
         &self,
         mut layouter: impl Layouter<F>,
@@ -244,7 +322,7 @@ struct MyCircuit<F: Field> {
 
 impl<F: Field> Circuit<F> for MyCircuit<F> {
     // Since we are using a single chip for everything, we can just reuse its config.
-    type Config = FieldConfig;
+    type Config = FieldConfig<F>;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
@@ -300,8 +378,44 @@ impl<F: Field> Circuit<F> for MyCircuit<F> {
     }
     // ANCHOR_END: circuit
 
+/// Exposes `a / b` and the zero-divisor flag.
+#[derive(Default)]
+struct DivCircuit<F: Field> {
+    a: Value<F>,
+    b: Value<F>,
+}
+
+impl<F: Field> Circuit<F> for DivCircuit<F> {
+    type Config = FieldConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config);
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+
+        let (c, flag) = field_chip.div(layouter.namespace(|| "a / b"), a, b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0)?;
+        field_chip.expose_public(layouter.namespace(|| "expose flag"), flag, 1)
+    }
+}
+
     fn main() {
-        use halo2_proofs::{dev::MockProver, pasta::Fp};
+        use halo2_proofs::{arithmetic::Field, dev::MockProver, pasta::Fp};
     
         // ANCHOR: test-circuit
         // The number of rows in our circuit cannot exceed 2^k. Since our example
@@ -336,5 +450,33 @@ impl<F: Field> Circuit<F> for MyCircuit<F> {
         assert!(prover.verify().is_err());
         //println!("public inputs: {:?}", public_inputs[0]);
         // ANCHOR_END: test-circuit
+
+        // Division gives a / b with the flag unset...
+        let circuit = DivCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![a * b.invert().unwrap(), Fp::zero()];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // ...and a zero divisor gives 0 with the flag set.
+        let circuit = DivCircuit {
+            a: Value::known(a),
+            b: Value::known(Fp::zero()),
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero(), Fp::one()]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero(), Fp::zero()]]).unwrap();
+        assert!(prover.verify().is_err());
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn simple_example() {
+        super::main();
+    }
 }
     
@@ -1,3 +1,9 @@
-mod example1;
-mod example2;
-mod example3;
\ No newline at end of file
+pub mod builder;
+pub mod compiler;
+pub mod example1;
+pub mod example2;
+pub mod example3;
+pub mod exponent;
+pub mod horner;
+pub mod inner_product;
+pub mod matmul;
@@ -0,0 +1,445 @@
+//! Writing circuits as plain Rust arithmetic. A `Context` hands out `Num`
+//! handles that support `+`, `-` and `*` with each other and with constants;
+//! the operations are only recorded, and laid out on a `NumericInstructions`
+//! chip by `Context::finalize`:
+//!
+//!   let ctx = Context::new();
+//!   let x = ctx.private(x);
+//!   let y = ctx.private(y);
+//!   let out = &x * &x + &x * &y * F::from(3) - y + F::from(5);
+//!   ctx.expose_public(&out, 0);
+//!   ctx.finalize(&chip, layouter.namespace(|| "out"))?;
+
+use std::{
+    cell::RefCell,
+    ops::{Add, Mul, Neg, Sub},
+    rc::Rc,
+};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Value},
+    plonk::Error,
+};
+
+use super::example2::{ArithmeticChip, ArithmeticInstructions, Number, Selectors};
+
+// ANCHOR: instructions
+pub trait NumericInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num: Clone;
+
+    /// Loads a number into the circuit as a private input.
+    fn load_private(&self, layouter: impl Layouter<F>, a: Value<F>) -> Result<Self::Num, Error>;
+
+    /// Loads a number into the circuit as a fixed constant.
+    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a + b`.
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a - b`.
+    fn sub(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a * b`.
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a + constant`.
+    fn add_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a * constant`.
+    fn mul_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+// ANCHOR_END: instructions
+
+impl<F: FieldExt> NumericInstructions<F> for ArithmeticChip<F> {
+    type Num = Number<F>;
+
+    fn load_private(&self, layouter: impl Layouter<F>, a: Value<F>) -> Result<Self::Num, Error> {
+        ArithmeticInstructions::load_private(self, layouter, a)
+    }
+
+    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error> {
+        ArithmeticInstructions::load_constant(self, layouter, constant)
+    }
+
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        ArithmeticInstructions::add(self, layouter, a, b)
+    }
+
+    fn sub(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        ArithmeticInstructions::gate(self, layouter, a, b, Selectors::sub())
+    }
+
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        ArithmeticInstructions::mul(self, layouter, a, b)
+    }
+
+    fn add_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        ArithmeticInstructions::add_constant(self, layouter, a, constant)
+    }
+
+    fn mul_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        ArithmeticInstructions::mul_constant(self, layouter, a, constant)
+    }
+
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error> {
+        ArithmeticInstructions::expose_public(self, layouter, num, row)
+    }
+}
+
+/// A recorded operation, operands are indices of earlier operations.
+#[derive(Clone, Debug)]
+enum Op<F> {
+    Private(Value<F>),
+    Constant(F),
+    Add(usize, usize),
+    Sub(usize, usize),
+    Mul(usize, usize),
+    AddConstant(usize, F),
+    MulConstant(usize, F),
+}
+
+#[derive(Debug)]
+struct Tape<F> {
+    ops: Vec<Op<F>>,
+    public: Vec<(usize, usize)>,
+}
+
+/// Records the operations on its `Num`s until `finalize`.
+#[derive(Debug)]
+pub struct Context<F> {
+    tape: Rc<RefCell<Tape<F>>>,
+}
+
+/// A number recorded in a `Context`. Operations between numbers of different
+/// contexts panic.
+#[derive(Clone, Debug)]
+pub struct Num<F> {
+    index: usize,
+    tape: Rc<RefCell<Tape<F>>>,
+}
+
+impl<F: FieldExt> Default for Context<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: FieldExt> Context<F> {
+    pub fn new() -> Self {
+        Self {
+            tape: Rc::new(RefCell::new(Tape {
+                ops: vec![],
+                public: vec![],
+            })),
+        }
+    }
+
+    /// A private input.
+    pub fn private(&self, value: Value<F>) -> Num<F> {
+        push(&self.tape, Op::Private(value))
+    }
+
+    /// A fixed constant.
+    pub fn constant(&self, constant: F) -> Num<F> {
+        push(&self.tape, Op::Constant(constant))
+    }
+
+    /// Exposes `num` in row `row` of the instance column.
+    pub fn expose_public(&self, num: &Num<F>, row: usize) {
+        assert!(
+            Rc::ptr_eq(&self.tape, &num.tape),
+            "number of another context"
+        );
+        self.tape.borrow_mut().public.push((num.index, row));
+    }
+
+    /// Lays out the recorded operations on `chip`, in the order they were
+    /// recorded, and returns the chip's numbers indexed by `Num::index`.
+    pub fn finalize<C: NumericInstructions<F>>(
+        &self,
+        chip: &C,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<Vec<C::Num>, Error> {
+        let tape = self.tape.borrow();
+        let mut nums: Vec<C::Num> = Vec::with_capacity(tape.ops.len());
+
+        for (i, op) in tape.ops.iter().enumerate() {
+            let layouter = layouter.namespace(|| format!("op {}", i));
+            let num = match op {
+                Op::Private(value) => chip.load_private(layouter, *value)?,
+                Op::Constant(c) => chip.load_constant(layouter, *c)?,
+                Op::Add(a, b) => chip.add(layouter, nums[*a].clone(), nums[*b].clone())?,
+                Op::Sub(a, b) => chip.sub(layouter, nums[*a].clone(), nums[*b].clone())?,
+                Op::Mul(a, b) => chip.mul(layouter, nums[*a].clone(), nums[*b].clone())?,
+                Op::AddConstant(a, c) => chip.add_constant(layouter, nums[*a].clone(), *c)?,
+                Op::MulConstant(a, c) => chip.mul_constant(layouter, nums[*a].clone(), *c)?,
+            };
+            nums.push(num);
+        }
+
+        for (index, row) in &tape.public {
+            chip.expose_public(
+                layouter.namespace(|| format!("expose op {}", index)),
+                nums[*index].clone(),
+                *row,
+            )?;
+        }
+
+        Ok(nums)
+    }
+}
+
+impl<F> Num<F> {
+    /// Position of the operation that produced this number.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+fn push<F>(tape: &Rc<RefCell<Tape<F>>>, op: Op<F>) -> Num<F> {
+    let mut inner = tape.borrow_mut();
+    inner.ops.push(op);
+    Num {
+        index: inner.ops.len() - 1,
+        tape: tape.clone(),
+    }
+}
+
+fn binary<F>(a: &Num<F>, b: &Num<F>, op: fn(usize, usize) -> Op<F>) -> Num<F> {
+    assert!(
+        Rc::ptr_eq(&a.tape, &b.tape),
+        "numbers of different contexts"
+    );
+    push(&a.tape, op(a.index, b.index))
+}
+
+/// Implements `$trait` for all combinations of owned and borrowed numbers.
+macro_rules! impl_binary {
+    ($trait:ident, $method:ident, $op:expr) => {
+        impl<F: FieldExt> $trait<&Num<F>> for &Num<F> {
+            type Output = Num<F>;
+
+            fn $method(self, rhs: &Num<F>) -> Num<F> {
+                binary(self, rhs, $op)
+            }
+        }
+
+        impl<F: FieldExt> $trait<Num<F>> for &Num<F> {
+            type Output = Num<F>;
+
+            fn $method(self, rhs: Num<F>) -> Num<F> {
+                binary(self, &rhs, $op)
+            }
+        }
+
+        impl<F: FieldExt> $trait<&Num<F>> for Num<F> {
+            type Output = Num<F>;
+
+            fn $method(self, rhs: &Num<F>) -> Num<F> {
+                binary(&self, rhs, $op)
+            }
+        }
+
+        impl<F: FieldExt> $trait<Num<F>> for Num<F> {
+            type Output = Num<F>;
+
+            fn $method(self, rhs: Num<F>) -> Num<F> {
+                binary(&self, &rhs, $op)
+            }
+        }
+    };
+}
+
+impl_binary!(Add, add, Op::Add);
+impl_binary!(Sub, sub, Op::Sub);
+impl_binary!(Mul, mul, Op::Mul);
+
+/// Implements `$trait<F>` for owned and borrowed numbers, `$op` maps the
+/// constant operand to the one recorded.
+macro_rules! impl_constant {
+    ($trait:ident, $method:ident, $op:expr) => {
+        impl<F: FieldExt> $trait<F> for &Num<F> {
+            type Output = Num<F>;
+
+            fn $method(self, rhs: F) -> Num<F> {
+                push(&self.tape, $op(self.index, rhs))
+            }
+        }
+
+        impl<F: FieldExt> $trait<F> for Num<F> {
+            type Output = Num<F>;
+
+            fn $method(self, rhs: F) -> Num<F> {
+                (&self).$method(rhs)
+            }
+        }
+    };
+}
+
+impl_constant!(Add, add, Op::AddConstant);
+impl_constant!(Sub, sub, |a, c: F| Op::AddConstant(a, -c));
+impl_constant!(Mul, mul, Op::MulConstant);
+
+impl<F: FieldExt> Neg for &Num<F> {
+    type Output = Num<F>;
+
+    fn neg(self) -> Num<F> {
+        self * -F::one()
+    }
+}
+
+impl<F: FieldExt> Neg for Num<F> {
+    type Output = Num<F>;
+
+    fn neg(self) -> Num<F> {
+        -&self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arithmetic::example2::ArithmeticConfig;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, ConstraintSystem},
+    };
+
+    /// Proves knowledge of `x` and `y` such that
+    ///
+    ///   out = x^2 + 3·x·y - y + 5 - (x - 2)·(y + 1)
+    ///
+    /// with `out` public.
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        x: Value<F>,
+        y: Value<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+        type Config = ArithmeticConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+
+            ArithmeticChip::configure(meta, advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = ArithmeticChip::construct(config);
+
+            let ctx = Context::new();
+            let x = ctx.private(self.x);
+            let y = ctx.private(self.y);
+            let two = ctx.constant(F::from(2));
+
+            let out =
+                &x * &x + &x * &y * F::from(3) - &y + F::from(5) - (&x - two) * (y + F::one());
+            ctx.expose_public(&out, 0);
+
+            ctx.finalize(&chip, layouter.namespace(|| "out"))?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn builder() {
+        let (x, y) = (Fp::from(7), Fp::from(11));
+        let out =
+            x * x + Fp::from(3) * x * y - y + Fp::from(5) - (x - Fp::from(2)) * (y + Fp::one());
+
+        let circuit = MyCircuit {
+            x: Value::known(x),
+            y: Value::known(y),
+        };
+        let prover = MockProver::run(5, &circuit, vec![vec![out]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(5, &circuit, vec![vec![out + Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "numbers of different contexts")]
+    fn builder_mixed_contexts() {
+        let (a, b) = (Context::<Fp>::new(), Context::<Fp>::new());
+        let _ = a.constant(Fp::one()) + b.constant(Fp::one());
+    }
+}
@@ -0,0 +1,512 @@
+//! Compiles polynomial expressions over named variables into rows of the
+//! `ArithmeticChip` of example3: every `+` and `*` between two wires becomes an
+//! `add` or `mul` row, every operation with a constant an `add_c` or `mul_c`
+//! row, and every operand is copied in from the cell that produced it.
+//!
+//! Expressions are written as `u^2 + 3*u*v - (v + 5) / u`: integer literals,
+//! variables, `+`, `-`, `*`, `/`, `^` with an integer exponent and parentheses.
+//! Like the `div` instruction, dividing by zero gives zero and sets a flag;
+//! `compile` returns the flags of the wire divisions so the caller can expose
+//! or constrain them.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
+
+use super::example3::{ArithmeticChip, ArithmeticConfig, DivInstructions};
+
+/// Polynomial expression over named variables.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr<F> {
+    Var(String),
+    Const(F),
+    Neg(Box<Expr<F>>),
+    Add(Box<Expr<F>>, Box<Expr<F>>),
+    Sub(Box<Expr<F>>, Box<Expr<F>>),
+    Mul(Box<Expr<F>>, Box<Expr<F>>),
+    Div(Box<Expr<F>>, Box<Expr<F>>),
+    Pow(Box<Expr<F>>, u32),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// Unexpected character at the given byte offset.
+    UnexpectedChar(usize, char),
+    UnexpectedEnd,
+    /// An integer literal or exponent that does not fit in 64 / 32 bits.
+    InvalidNumber(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedChar(offset, c) => {
+                write!(f, "unexpected '{}' at offset {}", c, offset)
+            }
+            ParseError::UnexpectedEnd => write!(f, "unexpected end of expression"),
+            ParseError::InvalidNumber(n) => write!(f, "invalid number {}", n),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<F: FieldExt> Expr<F> {
+    pub fn var(name: &str) -> Self {
+        Expr::Var(name.to_string())
+    }
+
+    pub fn constant(value: u64) -> Self {
+        Expr::Const(F::from(value))
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser { input, pos: 0 };
+        let expr = parser.expr()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            Some(c) => Err(ParseError::UnexpectedChar(parser.pos, c)),
+            None => Ok(expr),
+        }
+    }
+
+    /// Evaluates the expression natively, `None` if a variable is missing.
+    pub fn evaluate(&self, vars: &HashMap<String, F>) -> Option<F> {
+        Some(match self {
+            Expr::Var(name) => *vars.get(name)?,
+            Expr::Const(c) => *c,
+            Expr::Neg(a) => -a.evaluate(vars)?,
+            Expr::Add(a, b) => a.evaluate(vars)? + b.evaluate(vars)?,
+            Expr::Sub(a, b) => a.evaluate(vars)? - b.evaluate(vars)?,
+            Expr::Mul(a, b) => a.evaluate(vars)? * b.evaluate(vars)?,
+            Expr::Div(a, b) => a.evaluate(vars)? * inv(b.evaluate(vars)?),
+            Expr::Pow(a, e) => a.evaluate(vars)?.pow_vartime([*e as u64]),
+        })
+    }
+}
+
+/// Recursive descent over
+///
+///   expr   = term (('+' | '-') term)*
+///   term   = unary (('*' | '/') unary)*
+///   unary  = '-' unary | power
+///   power  = atom ('^' number)?
+///   atom   = number | ident | '(' expr ')'
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// Consumes `c` if it is the next non-whitespace character.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| f(*c)) {
+            self.pos += c.len_utf8();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn expr<F: FieldExt>(&mut self) -> Result<Expr<F>, ParseError> {
+        let mut lhs = self.term()?;
+        loop {
+            if self.eat('+') {
+                lhs = Expr::Add(Box::new(lhs), Box::new(self.term()?));
+            } else if self.eat('-') {
+                lhs = Expr::Sub(Box::new(lhs), Box::new(self.term()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn term<F: FieldExt>(&mut self) -> Result<Expr<F>, ParseError> {
+        let mut lhs = self.unary()?;
+        loop {
+            if self.eat('*') {
+                lhs = Expr::Mul(Box::new(lhs), Box::new(self.unary()?));
+            } else if self.eat('/') {
+                lhs = Expr::Div(Box::new(lhs), Box::new(self.unary()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn unary<F: FieldExt>(&mut self) -> Result<Expr<F>, ParseError> {
+        if self.eat('-') {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else {
+            self.power()
+        }
+    }
+
+    fn power<F: FieldExt>(&mut self) -> Result<Expr<F>, ParseError> {
+        let base = self.atom()?;
+        if !self.eat('^') {
+            return Ok(base);
+        }
+
+        self.skip_whitespace();
+        let exponent = self.take_while(|c| c.is_ascii_digit());
+        match self.peek() {
+            _ if !exponent.is_empty() => exponent
+                .parse()
+                .map(|e| Expr::Pow(Box::new(base), e))
+                .map_err(|_| ParseError::InvalidNumber(exponent.to_string())),
+            Some(c) => Err(ParseError::UnexpectedChar(self.pos, c)),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+
+    fn atom<F: FieldExt>(&mut self) -> Result<Expr<F>, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let expr = self.expr()?;
+                if self.eat(')') {
+                    Ok(expr)
+                } else {
+                    match self.peek() {
+                        Some(c) => Err(ParseError::UnexpectedChar(self.pos, c)),
+                        None => Err(ParseError::UnexpectedEnd),
+                    }
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let number = self.take_while(|c| c.is_ascii_digit());
+                number
+                    .parse()
+                    .map(|n: u64| Expr::Const(F::from(n)))
+                    .map_err(|_| ParseError::InvalidNumber(number.to_string()))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+                Ok(Expr::Var(name.to_string()))
+            }
+            Some(c) => Err(ParseError::UnexpectedChar(self.pos, c)),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+}
+
+/// A compiled subexpression, constants are folded until they meet a wire.
+enum Operand<F: FieldExt> {
+    Cell(AssignedCell<F, F>),
+    Const(F),
+}
+
+/// Lays out `expr` with `vars` as inputs and returns the cell holding its value,
+/// with the zero-divisor flag of every division by a wire in evaluation order.
+///
+/// A variable missing from `vars` is a `Error::Synthesis`.
+#[allow(clippy::type_complexity)]
+pub fn compile<F: FieldExt>(
+    chip: &ArithmeticChip<F>,
+    mut layouter: impl Layouter<F>,
+    expr: &Expr<F>,
+    vars: &HashMap<String, AssignedCell<F, F>>,
+) -> Result<(AssignedCell<F, F>, Vec<AssignedCell<F, F>>), Error> {
+    let mut flags = vec![];
+    let out = match compile_operand(chip, &mut layouter, expr, vars, &mut flags)? {
+        Operand::Cell(cell) => cell,
+        Operand::Const(c) => chip.load_constant(layouter.namespace(|| "constant result"), c)?,
+    };
+
+    Ok((out, flags))
+}
+
+fn compile_operand<F: FieldExt>(
+    chip: &ArithmeticChip<F>,
+    layouter: &mut impl Layouter<F>,
+    expr: &Expr<F>,
+    vars: &HashMap<String, AssignedCell<F, F>>,
+    flags: &mut Vec<AssignedCell<F, F>>,
+) -> Result<Operand<F>, Error> {
+    use Operand::{Cell, Const};
+
+    let mut operand = |expr| compile_operand(chip, layouter, expr, vars, flags);
+
+    Ok(match expr {
+        Expr::Var(name) => Cell(vars.get(name).ok_or(Error::Synthesis)?.clone()),
+        Expr::Const(c) => Const(*c),
+        Expr::Neg(a) => match operand(a)? {
+            Const(a) => Const(-a),
+            Cell(a) => Cell(chip.mul_constant(layouter.namespace(|| "neg"), &a, -F::one())?),
+        },
+        Expr::Add(a, b) => match (operand(a)?, operand(b)?) {
+            (Const(a), Const(b)) => Const(a + b),
+            (Cell(a), Const(c)) | (Const(c), Cell(a)) => add_constant(chip, layouter, a, c)?,
+            (Cell(a), Cell(b)) => Cell(chip.add(layouter.namespace(|| "add"), &a, &b)?),
+        },
+        Expr::Sub(a, b) => match (operand(a)?, operand(b)?) {
+            (Const(a), Const(b)) => Const(a - b),
+            (Cell(a), Const(c)) => add_constant(chip, layouter, a, -c)?,
+            (Const(c), Cell(b)) => {
+                let b = chip.mul_constant(layouter.namespace(|| "neg"), &b, -F::one())?;
+                add_constant(chip, layouter, b, c)?
+            }
+            (Cell(a), Cell(b)) => {
+                let b = chip.mul_constant(layouter.namespace(|| "neg"), &b, -F::one())?;
+                Cell(chip.add(layouter.namespace(|| "sub"), &a, &b)?)
+            }
+        },
+        Expr::Mul(a, b) => match (operand(a)?, operand(b)?) {
+            (Const(a), Const(b)) => Const(a * b),
+            (Cell(a), Const(c)) | (Const(c), Cell(a)) => {
+                if c == F::one() {
+                    Cell(a)
+                } else {
+                    Cell(chip.mul_constant(layouter.namespace(|| "mul with constant"), &a, c)?)
+                }
+            }
+            (Cell(a), Cell(b)) => Cell(chip.mul(layouter.namespace(|| "mul"), &a, &b)?),
+        },
+        Expr::Div(a, b) => match (operand(a)?, operand(b)?) {
+            (Const(a), Const(b)) => Const(a * inv(b)),
+            (Cell(a), Const(c)) => {
+                Cell(chip.mul_constant(layouter.namespace(|| "div by constant"), &a, inv(c))?)
+            }
+            (a, Cell(b)) => {
+                let a = match a {
+                    Cell(a) => a,
+                    Const(c) => chip.load_constant(layouter.namespace(|| "dividend"), c)?,
+                };
+                let (out, flag) = chip.div(layouter.namespace(|| "div"), &a, &b)?;
+                flags.push(flag);
+                Cell(out)
+            }
+        },
+        Expr::Pow(a, e) => match operand(a)? {
+            Const(a) => Const(a.pow_vartime([*e as u64])),
+            Cell(_) if *e == 0 => Const(F::one()),
+            Cell(a) => {
+                // square and multiply from the bit below the leading one
+                let mut acc = a.clone();
+                for i in (0..31 - e.leading_zeros()).rev() {
+                    acc = chip.mul(layouter.namespace(|| "pow square"), &acc, &acc)?;
+                    if (e >> i) & 1 == 1 {
+                        acc = chip.mul(layouter.namespace(|| "pow multiply"), &acc, &a)?;
+                    }
+                }
+                Cell(acc)
+            }
+        },
+    })
+}
+
+/// `1/a`, or zero when `a` is zero.
+fn inv<F: FieldExt>(a: F) -> F {
+    a.invert().unwrap_or(F::zero())
+}
+
+fn add_constant<F: FieldExt>(
+    chip: &ArithmeticChip<F>,
+    layouter: &mut impl Layouter<F>,
+    a: AssignedCell<F, F>,
+    c: F,
+) -> Result<Operand<F>, Error> {
+    if c == F::zero() {
+        Ok(Operand::Cell(a))
+    } else {
+        chip.add_constant(layouter.namespace(|| "add with constant"), &a, c)
+            .map(Operand::Cell)
+    }
+}
+
+/// Proves `out = expr(private, public)` for any expression, with the public
+/// variables in rows `0..public.len()` of the instance column, followed by `out`
+/// and the zero-divisor flags returned by `compile`.
+#[derive(Clone, Debug)]
+pub struct ExpressionCircuit<F: FieldExt> {
+    pub expr: Expr<F>,
+    pub private: Vec<(String, Value<F>)>,
+    pub public: Vec<String>,
+}
+
+impl<F: FieldExt> ExpressionCircuit<F> {
+    pub fn new(expr: &str, private: &[(&str, F)], public: &[&str]) -> Result<Self, ParseError> {
+        Ok(Self {
+            expr: Expr::parse(expr)?,
+            private: private
+                .iter()
+                .map(|(name, value)| (name.to_string(), Value::known(*value)))
+                .collect(),
+            public: public.iter().map(|name| name.to_string()).collect(),
+        })
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for ExpressionCircuit<F> {
+    type Config = ArithmeticConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            private: self
+                .private
+                .iter()
+                .map(|(name, _)| (name.clone(), Value::unknown()))
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+
+        ArithmeticChip::configure(meta, advice, instance, constant)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = ArithmeticChip::construct(config);
+
+        let mut vars = HashMap::new();
+        for (name, value) in &self.private {
+            let cell =
+                chip.load_private(layouter.namespace(|| format!("load {}", name)), *value)?;
+            vars.insert(name.clone(), cell);
+        }
+        for (row, name) in self.public.iter().enumerate() {
+            let cell = chip.load_public(layouter.namespace(|| format!("load {}", name)), row)?;
+            vars.insert(name.clone(), cell);
+        }
+
+        let (out, flags) = compile(
+            &chip,
+            layouter.namespace(|| "expression"),
+            &self.expr,
+            &vars,
+        )?;
+        let row = self.public.len();
+        chip.expose_public(layouter.namespace(|| "expose out"), &out, row)?;
+        for (i, flag) in flags.iter().enumerate() {
+            chip.expose_public(layouter.namespace(|| "expose flag"), flag, row + 1 + i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{arithmetic::Field, dev::MockProver, pasta::Fp};
+
+    #[test]
+    fn parse() {
+        let u = || Box::new(Expr::var("u"));
+        let v = || Box::new(Expr::var("v"));
+        let c = |n| Box::new(Expr::constant(n));
+
+        assert_eq!(
+            Expr::<Fp>::parse("-u^2 + 3 * (u - v)").unwrap(),
+            Expr::Add(
+                Box::new(Expr::Neg(Box::new(Expr::Pow(u(), 2)))),
+                Box::new(Expr::Mul(c(3), Box::new(Expr::Sub(u(), v())))),
+            )
+        );
+        assert_eq!(Expr::<Fp>::parse("u + "), Err(ParseError::UnexpectedEnd));
+        assert_eq!(
+            Expr::<Fp>::parse("u ^ v"),
+            Err(ParseError::UnexpectedChar(4, 'v'))
+        );
+        assert_eq!(Expr::<Fp>::parse("(u + v"), Err(ParseError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn expression_circuit() {
+        let (x, y, z) = (Fp::from(6), Fp::from(7), Fp::from(11));
+        let circuit = ExpressionCircuit::new(
+            "x^3 - 2*x*y + (y - z)*(4 - x) + 2*3 - z",
+            &[("x", x), ("y", y)],
+            &["z"],
+        )
+        .unwrap();
+
+        let vars = [("x", x), ("y", y), ("z", z)]
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect();
+        let out = circuit.expr.evaluate(&vars).unwrap();
+        assert_eq!(
+            out,
+            x * x * x - Fp::from(2) * x * y + (y - z) * (Fp::from(4) - x) + Fp::from(6) - z
+        );
+
+        let public_inputs = vec![z, out];
+        let prover = MockProver::run(5, &circuit, vec![public_inputs.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        // the public variable is bound to the instance column as well
+        let wrong_inputs = vec![z + Fp::one(), out];
+        let prover = MockProver::run(5, &circuit, vec![wrong_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // dividing by a zero wire gives zero and sets its flag
+        let circuit = ExpressionCircuit::new(
+            "x / (y - 7) + (x + 3) / x + 1 / 2",
+            &[("x", x), ("y", y)],
+            &[],
+        )
+        .unwrap();
+        let out = circuit.expr.evaluate(&vars).unwrap();
+        assert_eq!(
+            out,
+            (x + Fp::from(3)) * x.invert().unwrap() + Fp::from(2).invert().unwrap()
+        );
+
+        let prover = MockProver::run(5, &circuit, vec![vec![out, Fp::one(), Fp::zero()]]).unwrap();
+        prover.assert_satisfied();
+        let prover = MockProver::run(5, &circuit, vec![vec![out, Fp::zero(), Fp::zero()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // x^1000 takes 9 squarings and 5 multiplications
+        let circuit = ExpressionCircuit::new("x^1000 + x^1 + x^0", &[("x", x)], &[]).unwrap();
+        let out = circuit.expr.evaluate(&vars).unwrap();
+        assert_eq!(out, x.pow_vartime([1000]) + x + Fp::one());
+
+        let prover = MockProver::run(5, &circuit, vec![vec![out]]).unwrap();
+        prover.assert_satisfied();
+        let prover = MockProver::run(5, &circuit, vec![vec![out + Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // unknown variables fail synthesis
+        let circuit = ExpressionCircuit::<Fp>::new("x + w", &[("x", x)], &[]).unwrap();
+        assert!(matches!(
+            MockProver::run(5, &circuit, vec![vec![]]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
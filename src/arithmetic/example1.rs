@@ -1,27 +1,33 @@
 use std::marker::PhantomData;
 
+use gadget::inv::{InvChip, InvConfig};
 use halo2_proofs::{
-    arithmetic::FieldExt, circuit::*, dev::MockProver, pasta::Fp, plonk::*, poly::Rotation
+    arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation
 };
 
+use super::example3::DivInstructions;
+
 #[derive(Debug, Clone)]
-struct ACell<F: FieldExt>(AssignedCell<F, F>);
+pub struct ACell<F: FieldExt>(pub AssignedCell<F, F>);
 
 #[derive(Debug, Clone)]
-struct ArithmeticConfig {
+pub struct ArithmeticConfig<F: FieldExt> {
     advice: [Column<Advice>; 3],
     instance: Column<Instance>,
     s_add: Selector,
     s_mul: Selector,
+    s_inv: Selector,
+    // lhs | flag | inv on the `s_inv` rows
+    inv: InvConfig<F>,
 }
 
-struct ArithmeticChip<F: FieldExt> {
-    config: ArithmeticConfig,
+pub struct ArithmeticChip<F: FieldExt> {
+    config: ArithmeticConfig<F>,
     _marker: PhantomData<F>,
 }
 
 impl<F: FieldExt>  ArithmeticChip<F>{
-    pub fn construct(config: ArithmeticConfig) -> Self {
+    pub fn construct(config: ArithmeticConfig<F>) -> Self {
         Self {config, _marker: PhantomData }
     }
 
@@ -29,7 +35,7 @@ impl<F: FieldExt>  ArithmeticChip<F>{
         meta: &mut ConstraintSystem<F>,
         advice: [Column<Advice>; 3],
         instance: Column<Instance>,
-    ) -> ArithmeticConfig {
+    ) -> ArithmeticConfig<F> {
         // enable equality to check the permutation on the specified columns
         for column in &advice{
             meta.enable_equality(*column);
@@ -39,6 +45,7 @@ impl<F: FieldExt>  ArithmeticChip<F>{
         
         let s_add = meta.selector();
         let s_mul = meta.selector();
+        let s_inv = meta.selector();
        // let s_add_c = meta.selector();
        // let s_mul_c = meta.selector();
 
@@ -60,6 +67,16 @@ impl<F: FieldExt>  ArithmeticChip<F>{
             vec![s_mul * (lhs * rhs - out)]
         });
 
+        // lhs | flag | inv, with inv = 1/lhs and flag = 0, or inv = 0 and flag = 1
+        // for a zero lhs
+        let inv = InvChip::configure(
+            meta,
+            |meta| meta.query_selector(s_inv),
+            |meta| meta.query_advice(advice[0], Rotation::cur()),
+            advice[1],
+            advice[2],
+        );
+
         // meta.create_gate("add_with_const", |meta|{
         //     let s_add_c = meta.query_selector(s_add_c);
         //     let lhs = meta.query_advice(advice[0], Rotation::cur());
@@ -73,11 +90,14 @@ impl<F: FieldExt>  ArithmeticChip<F>{
             advice,
             instance,
             s_add,
-            s_mul,      
+            s_mul,
+            s_inv,
+            inv,
         }
 
     }
 
+    #[allow(clippy::type_complexity)]
     pub fn assign_add (
         &self,
         mut layouter: impl  Layouter<F>,
@@ -115,6 +135,7 @@ impl<F: FieldExt>  ArithmeticChip<F>{
     })        
     }
 
+    #[allow(clippy::type_complexity)]
     pub fn assign_mul (
         &self,
         mut layouter: impl  Layouter<F>,
@@ -164,14 +185,67 @@ impl<F: FieldExt>  ArithmeticChip<F>{
     }
 }
 
+impl<F: FieldExt> Chip<F> for ArithmeticChip<F> {
+    type Config = ArithmeticConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> DivInstructions<F> for ArithmeticChip<F> {
+    type Num = ACell<F>;
+
+    fn inv(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        layouter.assign_region(|| "inv",
+            |mut region|{
+                self.config.s_inv.enable(&mut region, 0)?;
+
+                let lhs = a.0.copy_advice(|| "lhs", &mut region, self.config.advice[0], 0)?;
+
+                let inv_chip = InvChip::construct(self.config.inv.clone());
+                let (inv, flag) = inv_chip.assign(&mut region, 0, lhs.value().copied())?;
+
+                Ok((ACell(inv), ACell(flag)))
+            })
+    }
+
+    /// Returns `a * b`, unlike `assign_mul` with `a` and `b` copied into the row.
+    fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Self::Num,
+        b: &Self::Num,
+    ) -> Result<Self::Num, Error> {
+        layouter.assign_region(|| "mul",
+            |mut region|{
+                self.config.s_mul.enable(&mut region, 0)?;
+                let a = a.0.copy_advice(|| "a", &mut region, self.config.advice[0], 0)?;
+                let b = b.0.copy_advice(|| "b", &mut region, self.config.advice[1], 0)?;
+
+                let c_val = a.value().copied() * b.value();
+                region.assign_advice(|| "c", self.config.advice[2], 0, || c_val).map(ACell)
+            })
+    }
+}
+
 #[derive(Default)]
-struct ArithmeticCircuit<F> {
-    a: Value<F>,
-    b: Value<F>
+pub struct ArithmeticCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>
 }
 
 impl<F: FieldExt> Circuit<F> for ArithmeticCircuit<F> {
-    type Config = ArithmeticConfig;
+    type Config = ArithmeticConfig<F>;
 
     type FloorPlanner = SimpleFloorPlanner;
 
@@ -215,29 +289,87 @@ impl<F: FieldExt> Circuit<F> for ArithmeticCircuit<F> {
   }
 }
 
-fn main() {
-    let k = 4;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prove::prove_and_verify;
+    use halo2_proofs::{arithmetic::Field, dev::MockProver, pasta::Fp};
 
-    let a: Fp = Fp::from(2);
-    let b: Fp = Fp::from(1);
-    let c: Fp = a + b;
+    #[test]
+    fn arithmetic_example1() {
+        let k = 4;
 
-    let out = a * c;
+        let a = Fp::from(2);
+        let b = Fp::from(1);
+        let out = a * (a + b);
 
-    let circuit = ArithmeticCircuit {
-        a: Value::known(a),
-        b: Value::known(b)
-    };
+        let circuit = ArithmeticCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
 
-    let mut  _public_input = vec![out];
-    
+        let public_input = vec![out];
+
+        let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        let mut wrong_input = public_input.clone();
+        wrong_input[0] += Fp::one();
+        let prover = MockProver::run(k, &circuit, vec![wrong_input]).unwrap();
+        assert!(prover.verify().is_err());
+
+        assert!(prove_and_verify(k, circuit, &[&public_input]).is_ok());
+    }
+
+    /// Exposes `a / b` and the zero-divisor flag.
+    #[derive(Default)]
+    struct DivCircuit<F> {
+        a: Value<F>,
+        b: Value<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for DivCircuit<F> {
+        type Config = ArithmeticConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            ArithmeticCircuit::configure(meta)
+        }
 
-    let prover = MockProver::run(k, &circuit, vec![_public_input.clone()]).unwrap();
-    prover.assert_satisfied();
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = ArithmeticChip::construct(config);
 
-    println!("c = {:?}", out);
-    // _public_input[2] += Fp::one();
-    // let prover = MockProver::run(k, &circuit, vec![_public_input.clone()]).unwrap();
-    // prover.assert_satisfied();
+            // the add row only loads a and b
+            let (a, b, _) = chip.assign_add(layouter.namespace(|| "load"), self.a, self.b)?;
+            let (out, flag) = chip.div(layouter.namespace(|| "a / b"), &a, &b)?;
 
+            chip.expose_public(layouter.namespace(|| "expose out"), &out, 0)?;
+            chip.expose_public(layouter.namespace(|| "expose flag"), &flag, 1)
+        }
+    }
+
+    #[test]
+    fn div_and_inv() {
+        let k = 4;
+        let (a, b) = (Fp::from(3), Fp::from(4));
+
+        let circuit = DivCircuit { a: Value::known(a), b: Value::known(b) };
+        let public_inputs = vec![a * b.invert().unwrap(), Fp::zero()];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        prover.assert_satisfied();
+
+        // a zero divisor gives 0 with the flag set
+        let circuit = DivCircuit { a: Value::known(a), b: Value::known(Fp::zero()) };
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero(), Fp::one()]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero(), Fp::zero()]]).unwrap();
+        assert!(prover.verify().is_err());
+        let prover = MockProver::run(k, &circuit, vec![vec![a, Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
 }
@@ -1,106 +1,318 @@
-use group::ff::Field;
-use halo2_proofs::plonk::*;
-use halo2_proofs::poly::Rotation;
 use std::marker::PhantomData;
-use halo2_proofs::circuit::*;
+
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{AssignedCell, Chip, Layouter},
-    plonk::{Advice, Column, Error, Fixed, Instance},
+    circuit::{AssignedCell, Cell, Chip, Layouter, Region, SimpleFloorPlanner, Value},
+    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Error, Fixed, Instance},
+    poly::Rotation,
 };
 
-
-trait ArithmeticInstructions<F: FieldExt> {
+// ANCHOR: instructions
+pub trait ArithmeticInstructions<F: FieldExt>: Chip<F> {
     /// Variable representing a number.
     type Num;
 
-    fn raw_add<FM>(
+    /// Lays out a single row of the gate with the given selector values, where
+    /// `f` returns the values of `l`, `r` and `o`.
+    fn raw_gate<FM>(
         &self,
         layouter: &mut impl Layouter<F>,
+        selectors: Selectors<F>,
         f: FM,
     ) -> Result<(Cell, Cell, Cell), Error>
     where
         FM: FnMut() -> Value<(Assigned<F>, Assigned<F>, Assigned<F>)>;
 
+    /// Lays out a row `l + r = o`.
+    fn raw_add<FM>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        f: FM,
+    ) -> Result<(Cell, Cell, Cell), Error>
+    where
+        FM: FnMut() -> Value<(Assigned<F>, Assigned<F>, Assigned<F>)>;
 
+    /// Lays out a row `l * r = o`.
     fn raw_multiply<FM>(
-            &self,
-            layouter: &mut impl Layouter<F>,
-            f: FM,
+        &self,
+        layouter: &mut impl Layouter<F>,
+        f: FM,
     ) -> Result<(Cell, Cell, Cell), Error>
     where
         FM: FnMut() -> Value<(Assigned<F>, Assigned<F>, Assigned<F>)>;
 
     /// Loads a number into the circuit as a private input.
-    fn load_private(&self, layouter: impl Layouter<F>, a: Cell) -> Result<Cell, Error>;
+    fn load_private(&self, layouter: impl Layouter<F>, a: Value<F>) -> Result<Self::Num, Error>;
 
     /// Loads a number into the circuit as a fixed constant.
-    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Cell, Error>;
+    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error>;
 
-    // ensure two wire values are same
-    fn copy(&self, layouter: impl Layouter<F>, a: Cell, b: Cell) -> Result<(), Error>;
+    /// Returns `c = a + b`.
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
 
+    /// Returns `c = a * b`.
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
 
-    // /// Returns `c = a * b`.
-    // fn mul(
-    //     &self,
-    //     layouter: impl Layouter<F>,
-    //     a: Self::Num,
-    //     b: Self::Num,
-    // ) -> Result<Self::Num, Error>;
+    /// Returns `c = a + constant`.
+    fn add_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error>;
 
-    // fn add(
-    //     &self,
-    //     layouter: impl Layouter<F>,
-    //     a: Self::Num,
-    //     b: Self::Num,
-    // )-> Result<Self::Num, Error>;
+    /// Returns `c = a * constant`.
+    fn mul_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = (q_l·a + q_r·b + q_m·a·b + q_c) / q_o`, `q_o` must not be zero.
+    fn gate(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        selectors: Selectors<F>,
+    ) -> Result<Self::Num, Error>;
+
+    /// Ensures two wire values are the same.
+    fn copy(&self, layouter: impl Layouter<F>, a: Cell, b: Cell) -> Result<(), Error>;
 
     /// Exposes a number as a public input to the circuit.
     fn expose_public(
         &self,
         layouter: impl Layouter<F>,
-        cell: Cell,
+        num: Self::Num,
         row: usize,
     ) -> Result<(), Error>;
 }
 // ANCHOR_END: instructions
 
+/// Values of the fixed columns on one row of the gate
+///
+///   q_l·l + q_r·r + q_m·l·r - q_o·o + q_c = 0
+#[derive(Clone, Copy, Debug)]
+pub struct Selectors<F> {
+    pub q_l: F,
+    pub q_r: F,
+    pub q_m: F,
+    pub q_o: F,
+    pub q_c: F,
+}
+
+impl<F: FieldExt> Default for Selectors<F> {
+    fn default() -> Self {
+        Self {
+            q_l: F::zero(),
+            q_r: F::zero(),
+            q_m: F::zero(),
+            q_o: F::zero(),
+            q_c: F::zero(),
+        }
+    }
+}
+
+impl<F: FieldExt> Selectors<F> {
+    /// `l + r = o`
+    pub fn add() -> Self {
+        Self {
+            q_l: F::one(),
+            q_r: F::one(),
+            q_o: F::one(),
+            ..Self::default()
+        }
+    }
+
+    /// `l - r = o`
+    pub fn sub() -> Self {
+        Self {
+            q_l: F::one(),
+            q_r: -F::one(),
+            q_o: F::one(),
+            ..Self::default()
+        }
+    }
+
+    /// `l * r = o`
+    pub fn mul() -> Self {
+        Self {
+            q_m: F::one(),
+            q_o: F::one(),
+            ..Self::default()
+        }
+    }
+
+    /// `l + constant = o`
+    pub fn add_constant(constant: F) -> Self {
+        Self {
+            q_l: F::one(),
+            q_o: F::one(),
+            q_c: constant,
+            ..Self::default()
+        }
+    }
+
+    /// `l * constant = o`
+    pub fn mul_constant(constant: F) -> Self {
+        Self {
+            q_l: constant,
+            q_o: F::one(),
+            ..Self::default()
+        }
+    }
+}
+
 // ANCHOR: chip-config
 /// Chip state is stored in a config struct. This is generated by the chip
 /// during configuration, and then stored inside the chip.
 #[derive(Clone, Debug)]
-struct ArithmeticConfig {
-    /// For this chip, we will use two advice columns to implement our instructions.
+pub struct ArithmeticConfig {
+    /// For this chip, we will use three advice columns to implement our instructions.
     /// These are also the columns through which we communicate with other parts of
     /// the circuit.
-    l: Column<Advice>,
-    r: Column<Advice>,
-    o: Column<Advice>,
+    pub l: Column<Advice>,
+    pub r: Column<Advice>,
+    pub o: Column<Advice>,
 
     // Fixed columns
-    s_l: Column<Fixed>,
-    s_r: Column<Fixed>,
-    s_m: Column<Fixed>,
-    s_o: Column<Fixed>,
-    s_c: Column<Fixed>,
+    pub s_l: Column<Fixed>,
+    pub s_r: Column<Fixed>,
+    pub s_m: Column<Fixed>,
+    pub s_o: Column<Fixed>,
+    pub s_c: Column<Fixed>,
 
     // Public inputs
-    PI: Column<Instance>,
+    pub instance: Column<Instance>,
 }
+// ANCHOR_END: chip-config
 
-struct ArithmeticChip<F: FieldExt> {
+pub struct ArithmeticChip<F: FieldExt> {
     config: ArithmeticConfig,
     _marker: PhantomData<F>,
 }
+
 impl<F: FieldExt> ArithmeticChip<F> {
-    fn construct(config: ArithmeticConfig) -> Self {
+    pub fn construct(config: ArithmeticConfig) -> Self {
         Self {
             config,
             _marker: PhantomData,
         }
     }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+    ) -> ArithmeticConfig {
+        let [l, r, o] = advice;
+
+        meta.enable_equality(l);
+        meta.enable_equality(r);
+        meta.enable_equality(o);
+        meta.enable_equality(instance);
+
+        let s_l = meta.fixed_column();
+        let s_r = meta.fixed_column();
+        let s_m = meta.fixed_column();
+        let s_o = meta.fixed_column();
+        let s_c = meta.fixed_column();
+
+        meta.create_gate("mini_plonk", |meta| {
+            let l = meta.query_advice(l, Rotation::cur());
+            let r = meta.query_advice(r, Rotation::cur());
+            let o = meta.query_advice(o, Rotation::cur());
+
+            let s_l = meta.query_fixed(s_l, Rotation::cur());
+            let s_r = meta.query_fixed(s_r, Rotation::cur());
+            let s_o = meta.query_fixed(s_o, Rotation::cur());
+            let s_m = meta.query_fixed(s_m, Rotation::cur());
+            let s_c = meta.query_fixed(s_c, Rotation::cur());
+
+            vec![l.clone() * s_l + r.clone() * s_r + l * r * s_m - o * s_o + s_c]
+        });
+
+        ArithmeticConfig {
+            l,
+            r,
+            o,
+            s_l,
+            s_r,
+            s_m,
+            s_o,
+            s_c,
+            instance,
+        }
+    }
+
+    fn assign_selectors(
+        &self,
+        region: &mut Region<'_, F>,
+        selectors: Selectors<F>,
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        for (name, column, value) in [
+            ("l", config.s_l, selectors.q_l),
+            ("r", config.s_r, selectors.q_r),
+            ("m", config.s_m, selectors.q_m),
+            ("o", config.s_o, selectors.q_o),
+            ("c", config.s_c, selectors.q_c),
+        ] {
+            region.assign_fixed(|| name, column, 0, || Value::known(value))?;
+        }
+        Ok(())
+    }
+
+    /// Lays out one row with `a` copied into `l` and, if any, `b` copied into `r`.
+    fn row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Number<F>,
+        b: Option<&Number<F>>,
+        selectors: Selectors<F>,
+    ) -> Result<Number<F>, Error> {
+        let q_o_inv = Option::<F>::from(selectors.q_o.invert()).ok_or(Error::Synthesis)?;
+
+        layouter.assign_region(
+            || "row",
+            |mut region| {
+                self.assign_selectors(&mut region, selectors)?;
+
+                let lhs = a.0.copy_advice(|| "lhs", &mut region, self.config.l, 0)?;
+                let rhs = match b {
+                    Some(b) => {
+                        b.0.copy_advice(|| "rhs", &mut region, self.config.r, 0)?
+                            .value()
+                            .copied()
+                    }
+                    None => Value::known(F::zero()),
+                };
+
+                let value = lhs.value().copied().zip(rhs).map(|(l, r)| {
+                    (selectors.q_l * l + selectors.q_r * r + selectors.q_m * l * r + selectors.q_c)
+                        * q_o_inv
+                });
+
+                region
+                    .assign_advice(|| "out", self.config.o, 0, || value)
+                    .map(Number)
+            },
+        )
+    }
 }
+
 // ANCHOR: chip-impl
 impl<F: FieldExt> Chip<F> for ArithmeticChip<F> {
     type Config = ArithmeticConfig;
@@ -116,129 +328,184 @@ impl<F: FieldExt> Chip<F> for ArithmeticChip<F> {
 }
 // ANCHOR_END: chip-impl
 
-struct Number<F: FieldExt>(AssignedCell<F, F>);
+#[derive(Clone, Debug)]
+pub struct Number<F: FieldExt>(pub AssignedCell<F, F>);
 
+// ANCHOR: instructions-impl
 impl<F: FieldExt> ArithmeticInstructions<F> for ArithmeticChip<F> {
     type Num = Number<F>;
 
-    fn raw_add<FM>(
+    fn raw_gate<FM>(
         &self,
         layouter: &mut impl Layouter<F>,
+        selectors: Selectors<F>,
         mut f: FM,
     ) -> Result<(Cell, Cell, Cell), Error>
     where
-        FM: FnMut() -> Value<(Assigned<F>, Assigned<F>, Assigned<F>)> {
-            layouter.assign_region(
-                || "add",
-                |mut region| {
-                    let mut values = None;
-                    let lhs = region.assign_advice(
-                        || "lhs",
-                        self.config.l,
-                        0,
-                        || {
-                            values = Some(f());
-                            values.unwrap().map(|v| v.0)
-                        },
-                    )?;
-                    let rhs = region.assign_advice(
-                        || "rhs",
-                        self.config.r,
-                        0,
-                        || values.unwrap().map(|v| v.1),
-                    )?;
-    
-                    let out = region.assign_advice(
-                        || "out",
-                        self.config.o,
-                        0,
-                        || values.unwrap().map(|v| v.2),
-                    )?;
-    
-                    region.assign_fixed(|| "l", self.config.s_l, 0, || Value::known(F::one()))?;
-                    region.assign_fixed(|| "r", self.config.s_r, 0, || Value::known(F::one()))?;
-                    region.assign_fixed(|| "o", self.config.s_o, 0, || Value::known(F::one()))?;
-    
-                    Ok((lhs.cell(), rhs.cell(), out.cell()))
-                },
-            )
+        FM: FnMut() -> Value<(Assigned<F>, Assigned<F>, Assigned<F>)>,
+    {
+        layouter.assign_region(
+            || "raw gate",
+            |mut region| {
+                let values = f();
+                let lhs =
+                    region.assign_advice(|| "lhs", self.config.l, 0, || values.map(|v| v.0))?;
+                let rhs =
+                    region.assign_advice(|| "rhs", self.config.r, 0, || values.map(|v| v.1))?;
+                let out =
+                    region.assign_advice(|| "out", self.config.o, 0, || values.map(|v| v.2))?;
+
+                self.assign_selectors(&mut region, selectors)?;
+
+                Ok((lhs.cell(), rhs.cell(), out.cell()))
+            },
+        )
+    }
+
+    fn raw_add<FM>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        f: FM,
+    ) -> Result<(Cell, Cell, Cell), Error>
+    where
+        FM: FnMut() -> Value<(Assigned<F>, Assigned<F>, Assigned<F>)>,
+    {
+        self.raw_gate(layouter, Selectors::add(), f)
     }
-    
+
     fn raw_multiply<FM>(
         &self,
         layouter: &mut impl Layouter<F>,
-        mut f: FM,
+        f: FM,
     ) -> Result<(Cell, Cell, Cell), Error>
     where
         FM: FnMut() -> Value<(Assigned<F>, Assigned<F>, Assigned<F>)>,
     {
+        self.raw_gate(layouter, Selectors::mul(), f)
+    }
+
+    fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+    ) -> Result<Self::Num, Error> {
+        // all selectors are left at zero, the row is unconstrained
         layouter.assign_region(
-            || "mul",
+            || "load private",
             |mut region| {
-                let mut values = None;
-                let lhs = region.assign_advice(
-                    || "lhs",
-                    self.config.l,
-                    0,
-                    || {
-                        values = Some(f());
-                        values.unwrap().map(|v| v.0)
-                    },
-                )?;
-                let rhs = region.assign_advice(
-                    || "rhs",
-                    self.config.r,
-                    0,
-                    || values.unwrap().map(|v| v.1),
-                )?;
+                region
+                    .assign_advice(|| "private input", self.config.l, 0, || a)
+                    .map(Number)
+            },
+        )
+    }
 
-                let out = region.assign_advice(
-                    || "out",
-                    self.config.o,
-                    0,
-                    || values.unwrap().map(|v| v.2),
+    fn load_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        // l - constant = 0
+        layouter.assign_region(
+            || "load constant",
+            |mut region| {
+                self.assign_selectors(
+                    &mut region,
+                    Selectors {
+                        q_l: F::one(),
+                        q_c: -constant,
+                        ..Selectors::default()
+                    },
                 )?;
 
-                region.assign_fixed(|| "m", self.config.s_m, 0, || Value::known(F::one()))?;
-                region.assign_fixed(|| "o", self.config.s_o, 0, || Value::known(F::one()))?;
-
-                Ok((lhs.cell(), rhs.cell(), out.cell()))
+                region
+                    .assign_advice(|| "constant", self.config.l, 0, || Value::known(constant))
+                    .map(Number)
             },
         )
     }
-    fn load_private(&self, _layouter: impl Layouter<F>, a: Cell) -> Result<Cell, Error> {
-        todo!()
+
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        self.row(layouter, &a, Some(&b), Selectors::add())
+    }
+
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        self.row(layouter, &a, Some(&b), Selectors::mul())
     }
-    
-    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Cell, Error> {
-        todo!()
+
+    fn add_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        self.row(layouter, &a, None, Selectors::add_constant(constant))
     }
-    
+
+    fn mul_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        self.row(layouter, &a, None, Selectors::mul_constant(constant))
+    }
+
+    fn gate(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        selectors: Selectors<F>,
+    ) -> Result<Self::Num, Error> {
+        self.row(layouter, &a, Some(&b), selectors)
+    }
+
     fn copy(&self, mut layouter: impl Layouter<F>, a: Cell, b: Cell) -> Result<(), Error> {
-        layouter.assign_region(|| "copy",
+        layouter.assign_region(
+            || "copy",
             |mut region| {
+                // MockProver cannot report failures while a region has no cells
+                self.assign_selectors(&mut region, Selectors::default())?;
                 region.constrain_equal(a, b)
-    })
-}
-    
+            },
+        )
+    }
+
     fn expose_public(
         &self,
         mut layouter: impl Layouter<F>,
-        cell: Cell,
+        num: Self::Num,
         row: usize,
     ) -> Result<(), Error> {
         let config = self.config();
 
-        layouter.constrain_instance(cell, config.PI, row)
+        layouter.constrain_instance(num.0.cell(), config.instance, row)
     }
 }
 // ANCHOR_END: instructions-impl
 
+// ANCHOR: circuit
+/// Proves knowledge of `x` and `y` such that
+///
+///   out = x^2 + x·y + 2·y + constant + (x + y + x·y) + 5
+///
+/// with `out` public.
 #[derive(Default)]
-struct ArithmeticCircuit<F: FieldExt> {
-    constant: F,
-    x: Value<F>,
-    y: Value<F>,
+pub struct ArithmeticCircuit<F: FieldExt> {
+    pub constant: F,
+    pub x: Value<F>,
+    pub y: Value<F>,
 }
 
 impl<F: FieldExt> Circuit<F> for ArithmeticCircuit<F> {
@@ -247,114 +514,116 @@ impl<F: FieldExt> Circuit<F> for ArithmeticCircuit<F> {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self {
+            constant: self.constant,
+            ..Self::default()
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let l: Column<Advice> = meta.advice_column();
-        let r: Column<Advice> = meta.advice_column();
-        let o: Column<Advice> = meta.advice_column();
-
-        meta.enable_equality(l);
-        meta.enable_equality(r);
-        meta.enable_equality(o);
-
-        let s_l: Column<Fixed> = meta.fixed_column();
-        let s_r: Column<Fixed> = meta.fixed_column();
-        let s_m: Column<Fixed> = meta.fixed_column();
-        let s_o: Column<Fixed> = meta.fixed_column();
-        let s_c: Column<Fixed> = meta.fixed_column();
-        #[allow(non_snake_case)]
-        let PI: Column<Instance> = meta.instance_column();
-        meta.enable_equality(PI);
-
-        meta.create_gate("mini_plonk", |meta| {
-            let l = meta.query_advice(l, Rotation::cur());
-            let r = meta.query_advice(r, Rotation::cur());
-            let o = meta.query_advice(o, Rotation::cur());
-
-            let s_l  = meta.query_fixed(s_l, Rotation::cur());
-            let s_r  = meta.query_fixed(s_r, Rotation::cur());
-            let s_o  = meta.query_fixed(s_o, Rotation::cur());
-            let s_m  = meta.query_fixed(s_m, Rotation::cur());
-            let s_c  = meta.query_fixed(s_c, Rotation::cur());
-
-            vec![l.clone()*s_l + r.clone() * s_r + l*r*s_m + (o*s_o* (-F::one())) + s_c]
-        });
-        
-
-        ArithmeticConfig {
-            l,
-            r,
-            o,
-            s_l,
-            s_r,
-            s_o,
-            s_m,
-            s_c,
-            PI,
-        }
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+
+        ArithmeticChip::configure(meta, advice, instance)
     }
 
-
-fn synthesize(
+    fn synthesize(
         &self,
         config: Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
         let arith_chip = ArithmeticChip::<F>::construct(config);
 
-        let x: Value<Assigned<F>> = self.x.into();
-        let y: Value<Assigned<F>> = self.y.into();
-        let _consty = Assigned::from(self.constant);
-
-        let (a0, b0, c0) = arith_chip.raw_multiply(&mut layouter,  || x.map(|x| (x,x,x*x)))?;
-       // arith_chip.copy(layouter, a0, b0)?;
-
-        let (a1, b1, _c1) = arith_chip.raw_multiply(&mut layouter, || y.map(|y| (y,y,y*y)))?;
-       // arith_chip.copy(layouter, a1, b1)?;
+        let x = arith_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+        let y = arith_chip.load_private(layouter.namespace(|| "load y"), self.y)?;
+        let constant =
+            arith_chip.load_constant(layouter.namespace(|| "load constant"), self.constant)?;
+
+        // x^2 from a raw row, wired back to x with copy constraints
+        let x_assigned: Value<Assigned<F>> = self.x.into();
+        let (a0, b0, c0) =
+            arith_chip.raw_multiply(&mut layouter, || x_assigned.map(|x| (x, x, x * x)))?;
+        arith_chip.copy(layouter.namespace(|| "x = a0"), x.0.cell(), a0)?;
+        arith_chip.copy(layouter.namespace(|| "x = b0"), x.0.cell(), b0)?;
+        let x2 = arith_chip.load_private(
+            layouter.namespace(|| "load x^2"),
+            self.x.map(|x| x.square()),
+        )?;
+        arith_chip.copy(layouter.namespace(|| "x^2 = c0"), x2.0.cell(), c0)?;
+
+        let xy = arith_chip.mul(layouter.namespace(|| "x * y"), x.clone(), y.clone())?;
+        let y2 = arith_chip.mul_constant(layouter.namespace(|| "2 * y"), y.clone(), F::from(2))?;
+
+        let sum = arith_chip.add(layouter.namespace(|| "x^2 + xy"), x2, xy)?;
+        let sum = arith_chip.add(layouter.namespace(|| "+ 2y"), sum, y2)?;
+        let sum = arith_chip.add(layouter.namespace(|| "+ constant"), sum, constant)?;
+
+        // x + y + x·y in a single custom row
+        let custom = arith_chip.gate(
+            layouter.namespace(|| "x + y + xy"),
+            x,
+            y,
+            Selectors {
+                q_l: F::one(),
+                q_r: F::one(),
+                q_m: F::one(),
+                q_o: F::one(),
+                q_c: F::zero(),
+            },
+        )?;
+        let out = arith_chip.add(layouter.namespace(|| "out"), sum, custom)?;
+        let out = arith_chip.add_constant(layouter.namespace(|| "+ 5"), out, F::from(5))?;
 
-       //let (a1, b1, c1) = arith_chip.raw_multiply(&mut layouter, ||(|x,y| (x,y,x*y)))?;
-      
-        Ok(())
+        arith_chip.expose_public(layouter.namespace(|| "expose out"), out, 0)
     }
 }
-    // ANCHOR_END: circuit
+// ANCHOR_END: circuit
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prove::prove_and_verify;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
 
-    fn main() {
-        use halo2_proofs::{dev::MockProver, pasta::Fp};
-    
+    #[test]
+    fn arithmetic_example2() {
         // ANCHOR: test-circuit
         // The number of rows in our circuit cannot exceed 2^k. Since our example
         // circuit is very small, we can pick a very small value here.
-        let k = 4;
-    
+        let k = 5;
+
         // Prepare the private and public inputs to the circuit!
         let constant = Fp::from(7);
-        let a = Fp::from(2);
-        let b = Fp::from(3);
-        let c = a * b;
-    
+        let x = Fp::from(2);
+        let y = Fp::from(3);
+        let out = x * x + x * y + Fp::from(2) * y + constant + (x + y + x * y) + Fp::from(5);
+
         // Instantiate the circuit with the private inputs.
         let circuit = ArithmeticCircuit {
             constant,
-            x: Value::known(a),
-            y: Value::known(b),
+            x: Value::known(x),
+            y: Value::known(y),
         };
-    
-        // Arrange the public input. We expose the multiplication result in row 0
+
+        // Arrange the public input. We expose the result in row 0
         // of the instance column, so we position it there in our public inputs.
-        let public_inputs = vec![c];
-        println!("public inputs: {:?}", public_inputs);
-    
+        let public_inputs = vec![out];
+
         // Given the correct public input, our circuit will verify.
         let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
 
         // If we try some other public input, the proof will fail!
-        // public_inputs[0] += Fp::one();
-        // let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
-        // assert!(prover.verify().is_err());
-        //println!("public inputs: {:?}", public_inputs[0]);
+        let mut wrong_inputs = public_inputs.clone();
+        wrong_inputs[0] += Fp::one();
+        let prover = MockProver::run(k, &circuit, vec![wrong_inputs]).unwrap();
+        assert!(prover.verify().is_err());
         // ANCHOR_END: test-circuit
+
+        assert!(prove_and_verify(k, circuit, &[&public_inputs]).is_ok());
+    }
 }
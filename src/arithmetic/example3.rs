@@ -1,7 +1,5 @@
-use std::env;
-use group::ff::Field;
 use std::marker::PhantomData;
-use halo2_proofs::circuit::{AssignedCell, Value};
+use gadget::inv::{InvChip, InvConfig};
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::*,
@@ -9,39 +7,80 @@ use halo2_proofs::{
     poly::Rotation,
 };
 
+use super::compiler::{compile, Expr};
+
+/// Division with a flagged zero divisor: a zero divisor gives the result 0 and
+/// sets the flag, instead of leaving the circuit unsatisfiable.
+pub trait DivInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `(1/a, 0)`, or `(0, 1)` when `a` is zero.
+    fn inv(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error>;
+
+    /// Returns `a * b`.
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Num,
+        b: &Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `(a/b, 0)`, or `(0, 1)` when `b` is zero.
+    fn div(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Self::Num,
+        b: &Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        let (inv, flag) = self.inv(layouter.namespace(|| "1 / b"), b)?;
+        let out = self.mul(layouter.namespace(|| "a * (1 / b)"), a, &inv)?;
+
+        Ok((out, flag))
+    }
+}
+
 // specify necessary columns in the main table
 #[derive(Clone, Debug)]
-struct ArithmeticConfig {
-    advice: [Column<Advice>; 3],
-    instance: Column<Instance>,
-    constant: Column<Fixed>,
+pub struct ArithmeticConfig<F: FieldExt> {
+    pub advice: [Column<Advice>; 3],
+    pub instance: Column<Instance>,
+    pub constant: Column<Fixed>,
 
     // selectors
-    s_add: Selector,
-    s_mul: Selector,
-    s_add_c: Selector,
-    s_mul_c: Selector,
+    pub s_add: Selector,
+    pub s_mul: Selector,
+    pub s_add_c: Selector,
+    pub s_mul_c: Selector,
+    pub s_inv: Selector,
+
+    // lhs | flag | inv on the `s_inv` rows
+    pub inv: InvConfig<F>,
 }
 
-struct ArithmeticChip<F: FieldExt> {
-    config: ArithmeticConfig,
+pub struct ArithmeticChip<F: FieldExt> {
+    config: ArithmeticConfig<F>,
     _marker: PhantomData<F>,
 }
 
 impl<F: FieldExt> ArithmeticChip<F> {
-    fn construct(config: ArithmeticConfig) -> Self {
+    pub fn construct(config: ArithmeticConfig<F>) -> Self {
         Self {
             config,
             _marker: PhantomData,
         }
     }
 
-    fn configure(
+    pub fn configure(
         meta: &mut ConstraintSystem<F>,
         advice: [Column<Advice>; 3],
         instance: Column<Instance>,
         constant: Column<Fixed>,
-    ) -> ArithmeticConfig {
+    ) -> ArithmeticConfig<F> {
         // specify columns used for proving copy constraints
         meta.enable_equality(instance);
         meta.enable_constant(constant);
@@ -54,6 +93,7 @@ impl<F: FieldExt> ArithmeticChip<F> {
         let s_mul = meta.selector();
         let s_add_c = meta.selector();
         let s_mul_c = meta.selector();
+        let s_inv = meta.selector();
 
         // Define our multiplication gate!
         meta.create_gate("mul", |meta| {
@@ -72,7 +112,7 @@ impl<F: FieldExt> ArithmeticChip<F> {
             let out = meta.query_advice(advice[2], Rotation::cur());
             let s_add = meta.query_selector(s_add);
 
-            vec![s_add * (lhs * rhs - out)]
+            vec![s_add * (lhs + rhs - out)]
         });
         
         // define addition with constant gate
@@ -93,6 +133,19 @@ impl<F: FieldExt> ArithmeticChip<F> {
             vec![s_mul_c * (lhs * fixed - out)]
         });
 
+        // define inverse gate, a zero lhs has the inverse 0 and the flag set
+        //
+        //  lhs | flag | inv | s_inv
+        //   a  |  0   | 1/a |   1
+        //   0  |  1   |  0  |   1
+        let inv = InvChip::configure(
+            meta,
+            |meta| meta.query_selector(s_inv),
+            |meta| meta.query_advice(advice[0], Rotation::cur()),
+            advice[1],
+            advice[2],
+        );
+
         ArithmeticConfig {
             advice,
             instance,
@@ -100,15 +153,158 @@ impl<F: FieldExt> ArithmeticChip<F> {
             s_mul,
             s_add,
             s_add_c,
-            s_mul_c
+            s_mul_c,
+            s_inv,
+            inv,
         }
     }
+
+    /// Loads a number into the circuit as a private input.
+    pub fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "load private",
+            |mut region| region.assign_advice(|| "private input", self.config.advice[0], 0, || value),
+        )
+    }
+
+    /// Loads row `row` of the instance column into the circuit.
+    pub fn load_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        row: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "load public",
+            |mut region| {
+                region.assign_advice_from_instance(
+                    || "public input",
+                    self.config.instance,
+                    row,
+                    self.config.advice[0],
+                    0,
+                )
+            },
+        )
+    }
+
+    /// Loads a number into the circuit as a fixed constant.
+    pub fn load_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        constant: F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "load constant",
+            |mut region| {
+                region.assign_advice_from_constant(|| "constant", self.config.advice[0], 0, constant)
+            },
+        )
+    }
+
+    /// Returns `a + b`, with `a` and `b` copied into the row.
+    pub fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.binary(layouter, "add", self.config.s_add, a, b, |a, b| a + b)
+    }
+
+    /// Returns `a * b`, with `a` and `b` copied into the row.
+    pub fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.binary(layouter, "mul", self.config.s_mul, a, b, |a, b| a * b)
+    }
+
+    /// Returns `a + constant`, with `a` copied into the row.
+    pub fn add_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        constant: F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.with_constant(layouter, "add with constant", self.config.s_add_c, a, constant, |a, c| a + c)
+    }
+
+    /// Returns `a * constant`, with `a` copied into the row.
+    pub fn mul_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        constant: F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.with_constant(layouter, "mul with constant", self.config.s_mul_c, a, constant, |a, c| a * c)
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+
+    fn binary(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        selector: Selector,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        op: impl Fn(F, F) -> F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+
+                let lhs = a.copy_advice(|| "lhs", &mut region, self.config.advice[0], 0)?;
+                let rhs = b.copy_advice(|| "rhs", &mut region, self.config.advice[1], 0)?;
+
+                let value = lhs.value().zip(rhs.value()).map(|(a, b)| op(*a, *b));
+                region.assign_advice(|| "out", self.config.advice[2], 0, || value)
+            },
+        )
+    }
+
+    fn with_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        selector: Selector,
+        a: &AssignedCell<F, F>,
+        constant: F,
+        op: impl Fn(F, F) -> F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+
+                let lhs = a.copy_advice(|| "lhs", &mut region, self.config.advice[0], 0)?;
+                region.assign_fixed(|| "constant", self.config.constant, 0, || Value::known(constant))?;
+
+                let value = lhs.value().map(|a| op(*a, constant));
+                region.assign_advice(|| "out", self.config.advice[2], 0, || value)
+            },
+        )
+    }
 }
 
 
 // ANCHOR: chip-impl
 impl<F: FieldExt> Chip<F> for ArithmeticChip<F> {
-    type Config = ArithmeticConfig;
+    type Config = ArithmeticConfig<F>;
     type Loaded = ();
 
     fn config(&self) -> &Self::Config {
@@ -121,19 +317,48 @@ impl<F: FieldExt> Chip<F> for ArithmeticChip<F> {
 }
 // ANCHOR_END: chip-impl
 
-#[derive(Default)]
-struct MyCircuit<F: FieldExt> {
-    u: Value<F>,
-    v: Value<F>,
+impl<F: FieldExt> DivInstructions<F> for ArithmeticChip<F> {
+    type Num = AssignedCell<F, F>;
+
+    fn inv(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        layouter.assign_region(
+            || "inv",
+            |mut region| {
+                self.config.s_inv.enable(&mut region, 0)?;
+
+                let lhs = a.copy_advice(|| "lhs", &mut region, self.config.advice[0], 0)?;
+
+                let inv_chip = InvChip::construct(self.config.inv.clone());
+                inv_chip.assign(&mut region, 0, lhs.value().copied())
+            },
+        )
+    }
+
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Num,
+        b: &Self::Num,
+    ) -> Result<Self::Num, Error> {
+        ArithmeticChip::mul(self, layouter, a, b)
+    }
 }
 
-//#[derive(Clone)]
-//struct Number<F: Field>(AssignedCell<F, F>);
+/// The formula proven by `MyCircuit`.
+pub const FORMULA: &str = "u^2 + 3*u*v + v + 5";
 
-impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
-//    type Num = Number<F>;
+#[derive(Default)]
+pub struct MyCircuit<F: FieldExt> {
+    pub u: Value<F>,
+    pub v: Value<F>,
+}
 
-    type Config = ArithmeticConfig;
+impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+    type Config = ArithmeticConfig<F>;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
@@ -150,160 +375,113 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     fn synthesize(
         &self, config: Self::Config, mut layouter: impl Layouter<F>
     ) -> Result<(), Error> {
-        // handling multiplication region
-        let t1 = self.u * self.u;
-        let t2 = self.u * self.v;
-        let t3 = t2 * Value::known(F::from(3));
-
-        // define multiplication region
-        let (
-            (x_a1, x_b1, x_c1),
-            (x_a2, x_b2, x_c2),
-            (x_a3, x_c3)
-        ) = layouter.assign_region(
-            || "multiplication region",
-            |mut region| {
-                // first row
-                config.s_mul.enable(&mut region, 0)?;
-                let x_a1 = region.assign_advice(|| "x_a1",
-                    config.advice[0].clone(), 0, || self.u)?;
-                let x_b1 = region.assign_advice(|| "x_b1",
-                    config.advice[1].clone(), 0, || self.u)?;
-                let x_c1 = region.assign_advice(|| "x_c1",
-                    config.advice[2].clone(), 0, || t1)?;
-
-                // second row
-                config.s_mul.enable(&mut region, 1)?;
-                let x_a2 = region.assign_advice(|| "x_a2",
-                    config.advice[0].clone(), 1, || self.u)?;
-                let x_b2 = region.assign_advice(|| "x_b2",
-                    config.advice[1].clone(), 1, || self.v)?;
-                let x_c2 = region.assign_advice(|| "x_c2",
-                    config.advice[2].clone(), 1, || t2)?;
-
-                // third row
-                config.s_mul_c.enable(&mut region, 2)?;
-                let x_a3 = region.assign_advice(|| "x_a3",
-                    config.advice[0].clone(), 2, || t2)?;
-                region.assign_fixed(|| "constant 3",
-                    config.constant.clone(), 2, || Value::known(F::from(3)))?;
-                let x_c3 = region.assign_advice(|| "x_c3",
-                    config.advice[2].clone(), 2, || t3)?;
-
-                Ok((
-                    (x_a1.cell(), x_b1.cell(), x_c1.cell()),
-                    (x_a2.cell(), x_b2.cell(), x_c2.cell()),
-                    (x_a3.cell(), x_c3.cell())
-                ))
-            }
-        )?;
-
-        let t4 = t1 + t3;
-        let t5 = t4 + self.v;
-        let t6 = t5 + Value::known(F::from(5));
-
-        // define addition region
-        let (
-            (x_a4, x_b4, x_c4),
-            (x_a5, x_b5, x_c5),
-            (x_a6, x_c6)
-        ) = layouter.assign_region(
-            || "addition region",
-            |mut region| {
-                // first row
-                config.s_add.enable(&mut region, 0)?;
-                let x_a4 = region.assign_advice(|| "x_a4",
-                    config.advice[0].clone(), 0, || t1)?;
-                let x_b4 = region.assign_advice(|| "x_b4",
-                    config.advice[1].clone(), 0, || t3)?;
-                let x_c4 = region.assign_advice(|| "x_c4",
-                    config.advice[2].clone(), 0, || t4)?;
-
-                // second row
-                config.s_add.enable(&mut region, 1)?;
-                let x_a5 = region.assign_advice(|| "x_a5",
-                    config.advice[0].clone(), 1, || t4)?;
-                let x_b5 = region.assign_advice(|| "x_b5",
-                    config.advice[1].clone(), 1, || self.v)?;
-                let x_c5 = region.assign_advice(|| "x_c5",
-                    config.advice[2].clone(), 1, || t5)?;
-
-                // third row
-                config.s_add_c.enable(&mut region, 2)?;
-                let x_a6 = region.assign_advice(|| "x_a6",
-                    config.advice[0].clone(), 2, || t5)?;
-                region.assign_fixed(|| "constant 5",
-                    config.constant.clone(), 2, || Value::known((F::from(5))))?;
-                let x_c6 = region.assign_advice(|| "x_c6",
-                    config.advice[2].clone(), 2, || t6)?;
-                Ok((
-                    (x_a4.cell(), x_b4.cell(), x_c4.cell()),
-                    (x_a5.cell(), x_b5.cell(), x_c5.cell()),
-                    (x_a6.cell(), x_c6.cell())
-                ))
-            }
-        )?;
-
-        // t6 is result, assign instance
-        layouter.constrain_instance(x_c6, config.instance, 0)?;
-
-        // enforce copy constraints
-        layouter.assign_region(|| "equality",
-            |mut region| {
-                region.constrain_equal(x_a1, x_a2)?; // namely, x_a1 = x_a2
-                region.constrain_equal(x_a2, x_b1)?; // namely, x_a2 = x_b1
+        let chip = ArithmeticChip::construct(config);
+
+        let u = chip.load_private(layouter.namespace(|| "load u"), self.u)?;
+        let v = chip.load_private(layouter.namespace(|| "load v"), self.v)?;
+        let vars = [("u".to_string(), u), ("v".to_string(), v)].into_iter().collect();
+
+        // the rows and copy constraints are generated from the formula
+        let expr = Expr::parse(FORMULA).map_err(|_| Error::Synthesis)?;
+        let (out, flags) = compile(&chip, layouter.namespace(|| "formula"), &expr, &vars)?;
+        // only `out` is public, a division by a wire would leave its flag unchecked
+        if !flags.is_empty() {
+            return Err(Error::Synthesis);
+        }
 
-                region.constrain_equal(x_b2, x_b5)?; // namely, x_b2 = x_b5
+        // the result is public, assign instance
+        chip.expose_public(layouter.namespace(|| "expose out"), &out, 0)
+    }
+}
 
-                region.constrain_equal(x_a4, x_c1)?; // namely, x_a4 = x_c1
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prove::prove_and_verify;
+    use halo2_proofs::{arithmetic::Field, dev::MockProver, pasta::Fp};
+
+    #[test]
+    fn arithmetic_example3() {
+        // The number of rows in our circuit cannot exceed 2^k. Since our example
+        // circuit is very small, we can pick a very small value here.
+        let k = 4;
+
+        // Prepare the private and public inputs to the circuit!
+        let u = Fp::from(2);
+        let v = Fp::from(3);
+        let out = u * u + Fp::from(3) * u * v + v + Fp::from(5);
+
+        // Instantiate the circuit with the private inputs.
+        let circuit = MyCircuit {
+            u: Value::known(u),
+            v: Value::known(v),
+        };
+
+        // We expose the result in row 0 of the instance column.
+        let public_inputs = vec![out];
+
+        // Given the correct public input, our circuit will verify.
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // If we try some other public input, the proof will fail!
+        let mut wrong_inputs = public_inputs.clone();
+        wrong_inputs[0] += Fp::one();
+        let prover = MockProver::run(k, &circuit, vec![wrong_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+
+        assert!(prove_and_verify(k, circuit, &[&public_inputs]).is_ok());
+    }
 
-                region.constrain_equal(x_a3, x_c2)?; // namely, x_a3 = x_c2
+    /// Exposes `a / b` and the zero-divisor flag.
+    #[derive(Default)]
+    struct DivCircuit<F: FieldExt> {
+        a: Value<F>,
+        b: Value<F>,
+    }
 
-                region.constrain_equal(x_b4, x_c3)?; // namely, x_b4 = x_c3
+    impl<F: FieldExt> Circuit<F> for DivCircuit<F> {
+        type Config = ArithmeticConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
 
-                region.constrain_equal(x_a5, x_c4)?; // namely, x_a5 = x_c4
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
 
-                region.constrain_equal(x_a6, x_c5)?; // namely, x_a6 = x_c5
-                Ok(())
-            }
-        )?;
-        Ok(())
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            MyCircuit::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = ArithmeticChip::construct(config);
+
+            let a = chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+            let b = chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+            let (out, flag) = chip.div(layouter.namespace(|| "a / b"), &a, &b)?;
+
+            chip.expose_public(layouter.namespace(|| "expose out"), &out, 0)?;
+            chip.expose_public(layouter.namespace(|| "expose flag"), &flag, 1)
+        }
     }
-}
 
-fn main() {
-    use halo2_proofs::{dev::MockProver, pasta::Fp};
-
-    // ANCHOR: test-circuit
-    // The number of rows in our circuit cannot exceed 2^k. Since our example
-    // circuit is very small, we can pick a very small value here.
-    let k = 4;
-
-    // Prepare the private and public inputs to the circuit!
-    let constant = Fp::from(7);
-    let a = Fp::from(2);
-    let b = Fp::from(3);
-    let c = a * b;
-
-    // Instantiate the circuit with the private inputs.
-    let circuit = MyCircuit {
-        u: Value::known(a),
-        v: Value::known(b),
-    };
-
-    // Arrange the public input. We expose the multiplication result in row 0
-    // of the instance column, so we position it there in our public inputs.
-    let public_inputs = vec![c];
-    println!("public inputs: {:?}", public_inputs);
-
-    // Given the correct public input, our circuit will verify.
-    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
-    assert_eq!(prover.verify(), Ok(()));
-
-    // If we try some other public input, the proof will fail!
-    // public_inputs[0] += Fp::one();
-    // let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
-    // assert!(prover.verify().is_err());
-    //println!("public inputs: {:?}", public_inputs[0]);
-    // ANCHOR_END: test-circuit
-}
\ No newline at end of file
+    #[test]
+    fn div_and_inv() {
+        let k = 4;
+        let (a, b) = (Fp::from(3), Fp::from(4));
+
+        let circuit = DivCircuit { a: Value::known(a), b: Value::known(b) };
+        let public_inputs = vec![a * b.invert().unwrap(), Fp::zero()];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        prover.assert_satisfied();
+
+        // a zero divisor gives 0 with the flag set
+        let circuit = DivCircuit { a: Value::known(a), b: Value::known(Fp::zero()) };
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero(), Fp::one()]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero(), Fp::zero()]]).unwrap();
+        assert!(prover.verify().is_err());
+        let prover = MockProver::run(k, &circuit, vec![vec![a, Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,450 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+pub trait ExpInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Loads a number into the circuit as a private input.
+    fn load_private(&self, layouter: impl Layouter<F>, a: Value<F>) -> Result<Self::Num, Error>;
+
+    /// Loads a number into the circuit as a fixed constant.
+    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error>;
+
+    /// Returns `x^e` for an exponent fixed in the circuit.
+    fn pow_fixed(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        e: u64,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `x^e` for a private exponent of at most `bits` bits, with `bits`
+    /// below `F::NUM_BITS`.
+    fn pow(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        e: Self::Num,
+        bits: usize,
+    ) -> Result<Self::Num, Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+
+/// Both exponentiations run down a single region, with the base carried to the
+/// next row by the gates rather than by copy constraints.
+///
+/// A fixed exponent follows its binary addition chain, every row squares the
+/// accumulator or multiplies it by the base:
+///
+///   x | acc       | q_square | q_mul
+///   x | x         |    1     |
+///   x | x^2       |          |   1
+///   x | x^3       |
+///
+/// A private exponent is decomposed most significant bit first, and every row
+/// also accumulates the bits back into `e`:
+///
+///   x | acc       | bit | e_acc   | q_bit
+///   x | 1         | b_0 | 0       |   1
+///   x | b_0 ? x:1 | b_1 | b_0     |   1
+///   ...
+///   x | x^e       |     | e       |
+#[derive(Clone, Debug)]
+pub struct ExpConfig {
+    pub x: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub bit: Column<Advice>,
+    pub e_acc: Column<Advice>,
+    pub instance: Column<Instance>,
+
+    pub q_square: Selector,
+    pub q_mul: Selector,
+    pub q_bit: Selector,
+}
+
+pub struct ExpChip<F: FieldExt> {
+    config: ExpConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> ExpChip<F> {
+    pub fn construct(config: ExpConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> ExpConfig {
+        let [x, acc, bit, e_acc] = advice;
+
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+
+        let q_square = meta.selector();
+        let q_mul = meta.selector();
+        let q_bit = meta.selector();
+
+        meta.create_gate("square", |meta| {
+            let q_square = meta.query_selector(q_square);
+            let x_cur = meta.query_advice(x, Rotation::cur());
+            let x_next = meta.query_advice(x, Rotation::next());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+
+            Constraints::with_selector(
+                q_square,
+                [
+                    ("carry x", x_next - x_cur),
+                    ("square", acc_next - acc_cur.clone() * acc_cur),
+                ],
+            )
+        });
+
+        meta.create_gate("mul by x", |meta| {
+            let q_mul = meta.query_selector(q_mul);
+            let x_cur = meta.query_advice(x, Rotation::cur());
+            let x_next = meta.query_advice(x, Rotation::next());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+
+            Constraints::with_selector(
+                q_mul,
+                [
+                    ("carry x", x_next - x_cur.clone()),
+                    ("mul", acc_next - acc_cur * x_cur),
+                ],
+            )
+        });
+
+        meta.create_gate("square and multiply", |meta| {
+            let q_bit = meta.query_selector(q_bit);
+            let x_cur = meta.query_advice(x, Rotation::cur());
+            let x_next = meta.query_advice(x, Rotation::next());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let e_cur = meta.query_advice(e_acc, Rotation::cur());
+            let e_next = meta.query_advice(e_acc, Rotation::next());
+
+            let one = Expression::Constant(F::one());
+            let two = Expression::Constant(F::from(2));
+            // x when the bit is set, 1 otherwise
+            let factor = bit.clone() * x_cur.clone() + one.clone() - bit.clone();
+
+            Constraints::with_selector(
+                q_bit,
+                [
+                    ("bool", bit.clone() * (one - bit.clone())),
+                    ("carry x", x_next - x_cur),
+                    (
+                        "square and multiply",
+                        acc_next - acc_cur.clone() * acc_cur * factor,
+                    ),
+                    ("decompose", e_next - (two * e_cur + bit)),
+                ],
+            )
+        });
+
+        ExpConfig {
+            x,
+            acc,
+            bit,
+            e_acc,
+            instance,
+            q_square,
+            q_mul,
+            q_bit,
+        }
+    }
+}
+
+impl<F: FieldExt> Chip<F> for ExpChip<F> {
+    type Config = ExpConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> ExpInstructions<F> for ExpChip<F> {
+    type Num = AssignedCell<F, F>;
+
+    fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<Self::Num, Error> {
+        layouter.assign_region(
+            || "load private",
+            |mut region| region.assign_advice(|| "private input", self.config.x, 0, || value),
+        )
+    }
+
+    fn load_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        layouter.assign_region(
+            || "load constant",
+            |mut region| {
+                region.assign_advice_from_constant(|| "constant input", self.config.x, 0, constant)
+            },
+        )
+    }
+
+    fn pow_fixed(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        e: u64,
+    ) -> Result<Self::Num, Error> {
+        if e == 0 {
+            return self.load_constant(layouter, F::one());
+        }
+
+        let config = &self.config;
+        layouter.assign_region(
+            || "pow fixed",
+            |mut region| {
+                let mut acc = x.copy_advice(|| "acc", &mut region, config.acc, 0)?;
+                x.copy_advice(|| "x", &mut region, config.x, 0)?;
+
+                // the leading bit is x itself
+                let mut row = 0;
+                for i in (0..63 - e.leading_zeros() as usize).rev() {
+                    for multiply in [false, true] {
+                        let (selector, value) = if !multiply {
+                            (config.q_square, acc.value().map(|acc| acc.square()))
+                        } else if (e >> i) & 1 == 1 {
+                            (
+                                config.q_mul,
+                                acc.value().zip(x.value()).map(|(a, x)| *a * x),
+                            )
+                        } else {
+                            continue;
+                        };
+
+                        selector.enable(&mut region, row)?;
+                        row += 1;
+                        region.assign_advice(|| "x", config.x, row, || x.value().copied())?;
+                        acc = region.assign_advice(|| "acc", config.acc, row, || value)?;
+                    }
+                }
+
+                Ok(acc)
+            },
+        )
+    }
+
+    fn pow(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        e: Self::Num,
+        bits: usize,
+    ) -> Result<Self::Num, Error> {
+        let config = &self.config;
+
+        // with as many bits as the modulus, the bits of `e + p` recompose to `e`
+        if bits >= F::NUM_BITS as usize {
+            return Err(Error::Synthesis);
+        }
+
+        // most significant bit first, an exponent above `bits` bits has no witness
+        let e_bits = e.value().map(|e| {
+            let repr = e.to_repr();
+            let repr = repr.as_ref();
+            (0..bits)
+                .rev()
+                .map(|i| i / 8 < repr.len() && (repr[i / 8] >> (i % 8)) & 1 == 1)
+                .collect::<Vec<_>>()
+        });
+        e.value()
+            .zip(e_bits.as_ref())
+            .error_if_known_and(|(e, e_bits)| {
+                let recomposed = e_bits
+                    .iter()
+                    .fold(F::zero(), |acc, b| acc.double() + F::from(*b as u64));
+                recomposed != **e
+            })?;
+
+        layouter.assign_region(
+            || "pow",
+            |mut region| {
+                let mut x_cell = x.copy_advice(|| "x", &mut region, config.x, 0)?;
+                let mut acc =
+                    region.assign_advice_from_constant(|| "acc", config.acc, 0, F::one())?;
+                let mut e_acc =
+                    region.assign_advice_from_constant(|| "e_acc", config.e_acc, 0, F::zero())?;
+
+                for row in 0..bits {
+                    config.q_bit.enable(&mut region, row)?;
+
+                    let bit = e_bits.as_ref().map(|e_bits| e_bits[row]);
+                    region.assign_advice(
+                        || "bit",
+                        config.bit,
+                        row,
+                        || bit.map(|b| F::from(b as u64)),
+                    )?;
+
+                    let factor = x_cell
+                        .value()
+                        .zip(bit)
+                        .map(|(x, b)| if b { *x } else { F::one() });
+                    let value = acc.value().zip(factor).map(|(acc, f)| acc.square() * f);
+                    let e_value = e_acc
+                        .value()
+                        .zip(bit)
+                        .map(|(e, b)| e.double() + F::from(b as u64));
+
+                    x_cell = region.assign_advice(
+                        || "x",
+                        config.x,
+                        row + 1,
+                        || x_cell.value().copied(),
+                    )?;
+                    acc = region.assign_advice(|| "acc", config.acc, row + 1, || value)?;
+                    e_acc = region.assign_advice(|| "e_acc", config.e_acc, row + 1, || e_value)?;
+                }
+
+                // the bits recompose to the exponent
+                region.constrain_equal(e_acc.cell(), e.cell())?;
+
+                Ok(acc)
+            },
+        )
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(num.cell(), self.config.instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{arithmetic::Field, dev::MockProver, pasta::Fp};
+
+    /// Proves `x^E` for a fixed `E` and `x^e` for a private `e` of `BITS` bits,
+    /// with the public inputs `[x^E, x^e]`.
+    #[derive(Default)]
+    struct MyCircuit<F, const E: u64, const BITS: usize = 8> {
+        x: Value<F>,
+        e: Value<F>,
+    }
+
+    impl<F: FieldExt, const E: u64, const BITS: usize> Circuit<F> for MyCircuit<F, E, BITS> {
+        type Config = ExpConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            let constant = meta.fixed_column();
+
+            ExpChip::configure(meta, advice, instance, constant)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = ExpChip::construct(config);
+
+            let x = chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+            let e = chip.load_private(layouter.namespace(|| "load e"), self.e)?;
+
+            let fixed = chip.pow_fixed(layouter.namespace(|| "x^E"), x.clone(), E)?;
+            let private = chip.pow(layouter.namespace(|| "x^e"), x, e, BITS)?;
+
+            chip.expose_public(layouter.namespace(|| "expose x^E"), fixed, 0)?;
+            chip.expose_public(layouter.namespace(|| "expose x^e"), private, 1)
+        }
+    }
+
+    fn run<const E: u64>(x: u64, e: u64) {
+        let x = Fp::from(x);
+        let circuit = MyCircuit::<Fp, E> {
+            x: Value::known(x),
+            e: Value::known(Fp::from(e)),
+        };
+
+        let public_inputs = vec![x.pow_vartime([E]), x.pow_vartime([e])];
+        let prover = MockProver::run(6, &circuit, vec![public_inputs.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        for i in 0..2 {
+            let mut wrong_inputs = public_inputs.clone();
+            wrong_inputs[i] += Fp::one();
+            let prover = MockProver::run(6, &circuit, vec![wrong_inputs]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn pow() {
+        run::<13>(3, 200);
+        run::<1>(3, 1);
+        run::<0>(3, 0);
+        run::<0b1011_0110>(7, 255);
+    }
+
+    #[test]
+    fn pow_exponent_too_large() {
+        let circuit = MyCircuit::<Fp, 2> {
+            x: Value::known(Fp::from(3)),
+            e: Value::known(Fp::from(256)),
+        };
+        assert!(matches!(
+            MockProver::run(6, &circuit, vec![vec![]]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn pow_too_many_bits() {
+        let circuit = MyCircuit::<Fp, 2, 255> {
+            x: Value::known(Fp::from(3)),
+            e: Value::known(Fp::from(5)),
+        };
+        assert!(matches!(
+            MockProver::run(9, &circuit, vec![vec![]]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
@@ -0,0 +1,398 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+/// Computes `p(x)` natively, with the coefficients of `p` in ascending order.
+pub fn evaluate<F: FieldExt>(coeffs: &[F], x: F) -> F {
+    coeffs.iter().rev().fold(F::zero(), |acc, c| acc * x + c)
+}
+
+/// Horner's rule down a single accumulator column, starting from the leading
+/// coefficient of `p(x) = c_0 + c_1·x + ... + c_d·x^d`:
+///
+///   acc       | c       | x | q_horner
+///   c_d       |         | x |    1
+///   acc·x + c | c_{d-1} | x |    1
+///   ...
+///   p(x)      | c_0     | x |
+///
+/// The gate looks one row ahead, `acc_next = acc·x + c_next`, and carries `x`
+/// down without copy constraints.
+#[derive(Clone, Debug)]
+pub struct HornerConfig {
+    pub acc: Column<Advice>,
+    pub coeff: Column<Advice>,
+    pub x: Column<Advice>,
+    pub instance: Column<Instance>,
+    pub q_horner: Selector,
+}
+
+pub struct HornerChip<F: FieldExt> {
+    config: HornerConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> HornerChip<F> {
+    pub fn construct(config: HornerConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> HornerConfig {
+        let [acc, coeff, x] = advice;
+
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+
+        let q_horner = meta.selector();
+
+        meta.create_gate("horner", |meta| {
+            let q_horner = meta.query_selector(q_horner);
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            let c_next = meta.query_advice(coeff, Rotation::next());
+            let x_cur = meta.query_advice(x, Rotation::cur());
+            let x_next = meta.query_advice(x, Rotation::next());
+
+            Constraints::with_selector(
+                q_horner,
+                [
+                    ("carry x", x_next - x_cur.clone()),
+                    ("horner", acc_next - (acc_cur * x_cur + c_next)),
+                ],
+            )
+        });
+
+        HornerConfig {
+            acc,
+            coeff,
+            x,
+            instance,
+            q_horner,
+        }
+    }
+
+    /// Loads the coefficients of `p`, in ascending order, as private inputs.
+    pub fn load_coefficients(
+        &self,
+        mut layouter: impl Layouter<F>,
+        coeffs: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        layouter.assign_region(
+            || "load coefficients",
+            |mut region| {
+                coeffs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        region.assign_advice(|| format!("c_{}", i), self.config.coeff, i, || *c)
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Loads the coefficients of `p`, in ascending order, as fixed constants.
+    pub fn load_constant_coefficients(
+        &self,
+        mut layouter: impl Layouter<F>,
+        coeffs: &[F],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        layouter.assign_region(
+            || "load constant coefficients",
+            |mut region| {
+                coeffs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        region.assign_advice_from_constant(
+                            || format!("c_{}", i),
+                            self.config.coeff,
+                            i,
+                            *c,
+                        )
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Loads `x` as a private input.
+    pub fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "load x",
+            |mut region| region.assign_advice(|| "x", self.config.x, 0, || x),
+        )
+    }
+
+    /// Loads `x` from row `row` of the instance column.
+    pub fn load_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        row: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "load x",
+            |mut region| {
+                region.assign_advice_from_instance(
+                    || "x",
+                    self.config.instance,
+                    row,
+                    self.config.x,
+                    0,
+                )
+            },
+        )
+    }
+
+    /// Returns `p(x)` for the loaded coefficients, in ascending order.
+    ///
+    /// The empty polynomial evaluates to a constant `0`, as in [`evaluate`].
+    pub fn evaluate(
+        &self,
+        mut layouter: impl Layouter<F>,
+        coeffs: &[AssignedCell<F, F>],
+        x: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let (leading, rest) = match coeffs.split_last() {
+            Some(split) => split,
+            None => {
+                return layouter.assign_region(
+                    || "horner",
+                    |mut region| {
+                        region.assign_advice_from_constant(|| "p(x)", config.acc, 0, F::zero())
+                    },
+                )
+            }
+        };
+
+        layouter.assign_region(
+            || "horner",
+            |mut region| {
+                let mut acc = leading.copy_advice(|| "c_d", &mut region, config.acc, 0)?;
+                let mut x_cell = x.copy_advice(|| "x", &mut region, config.x, 0)?;
+
+                for (row, c) in rest.iter().rev().enumerate() {
+                    config.q_horner.enable(&mut region, row)?;
+
+                    let c = c.copy_advice(|| "c", &mut region, config.coeff, row + 1)?;
+                    let value = acc
+                        .value()
+                        .zip(x_cell.value())
+                        .zip(c.value())
+                        .map(|((acc, x), c)| *acc * x + c);
+
+                    x_cell = region.assign_advice(
+                        || "x",
+                        config.x,
+                        row + 1,
+                        || x_cell.value().copied(),
+                    )?;
+                    acc = region.assign_advice(|| "acc", config.acc, row + 1, || value)?;
+                }
+
+                Ok(acc)
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// Proves `y = p(x)` with the public inputs `[x, y]`.
+    #[derive(Clone, Default)]
+    struct PublicX<F> {
+        coeffs: Vec<Value<F>>,
+    }
+
+    /// Proves `y = p(x)` for a fixed `p` and a private `x`, with the public input `[y]`.
+    #[derive(Clone, Default)]
+    struct FixedP<F> {
+        coeffs: Vec<F>,
+        x: Value<F>,
+    }
+
+    /// Proves `0 = p(x)` for the empty `p` and a private `x`, with the public input `[0]`.
+    #[derive(Clone, Default)]
+    struct EmptyP<F> {
+        x: Value<F>,
+    }
+
+    fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> HornerConfig {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+
+        HornerChip::configure(meta, advice, instance, constant)
+    }
+
+    impl<F: FieldExt> Circuit<F> for PublicX<F> {
+        type Config = HornerConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                coeffs: vec![Value::unknown(); self.coeffs.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = HornerChip::construct(config);
+
+            let coeffs = chip.load_coefficients(layouter.namespace(|| "p"), &self.coeffs)?;
+            let x = chip.load_public(layouter.namespace(|| "x"), 0)?;
+            let y = chip.evaluate(layouter.namespace(|| "p(x)"), &coeffs, &x)?;
+
+            chip.expose_public(layouter.namespace(|| "y"), &y, 1)
+        }
+    }
+
+    impl<F: FieldExt> Circuit<F> for FixedP<F> {
+        type Config = HornerConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                coeffs: self.coeffs.clone(),
+                x: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = HornerChip::construct(config);
+
+            let coeffs =
+                chip.load_constant_coefficients(layouter.namespace(|| "p"), &self.coeffs)?;
+            let x = chip.load_private(layouter.namespace(|| "x"), self.x)?;
+            let y = chip.evaluate(layouter.namespace(|| "p(x)"), &coeffs, &x)?;
+
+            chip.expose_public(layouter.namespace(|| "y"), &y, 0)
+        }
+    }
+
+    impl<F: FieldExt> Circuit<F> for EmptyP<F> {
+        type Config = HornerConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = HornerChip::construct(config);
+
+            let x = chip.load_private(layouter.namespace(|| "x"), self.x)?;
+            let y = chip.evaluate(layouter.namespace(|| "p(x)"), &[], &x)?;
+
+            chip.expose_public(layouter.namespace(|| "y"), &y, 0)
+        }
+    }
+
+    #[test]
+    fn horner_native() {
+        let coeffs = [3, 2, 5, 1].map(Fp::from);
+        assert_eq!(evaluate(&coeffs, Fp::from(2)), Fp::from(3 + 4 + 20 + 8));
+        assert_eq!(evaluate(&[], Fp::from(2)), Fp::zero());
+    }
+
+    #[test]
+    fn horner_public_x() {
+        let coeffs = [3, 2, 5, 1, 0, 7].map(Fp::from);
+        let x = Fp::from(4);
+        let y = evaluate(&coeffs, x);
+
+        let circuit = PublicX {
+            coeffs: coeffs.iter().map(|c| Value::known(*c)).collect(),
+        };
+        let prover = MockProver::run(5, &circuit, vec![vec![x, y]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(5, &circuit, vec![vec![x + Fp::one(), y]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn horner_fixed_p() {
+        let coeffs = [3, 2, 5, 1].map(Fp::from).to_vec();
+        let x = Fp::from(9);
+        let y = evaluate(&coeffs, x);
+
+        let circuit = FixedP {
+            coeffs: coeffs.clone(),
+            x: Value::known(x),
+        };
+        let prover = MockProver::run(5, &circuit, vec![vec![y]]).unwrap();
+        prover.assert_satisfied();
+
+        // the same x under another polynomial
+        let circuit = FixedP {
+            coeffs: [3, 2, 5, 2].map(Fp::from).to_vec(),
+            x: Value::known(x),
+        };
+        let prover = MockProver::run(5, &circuit, vec![vec![y]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn horner_empty_p() {
+        let circuit = EmptyP {
+            x: Value::known(Fp::from(9)),
+        };
+        let prover = MockProver::run(5, &circuit, vec![vec![Fp::zero()]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(5, &circuit, vec![vec![Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+/// Computes `⟨a, b⟩` natively.
+pub fn inner_product<F: FieldExt>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).fold(F::zero(), |acc, (a, b)| acc + *a * b)
+}
+
+/// `⟨a, b⟩` in a single region, one row per entry:
+///
+///   a   | b   | acc             | q_ip
+///   a_0 | b_0 | 0               |  1
+///   a_1 | b_1 | a_0·b_0         |  1
+///   ...
+///       |     | ⟨a, b⟩          |
+///
+/// with `acc_next = acc + a·b`.
+#[derive(Clone, Debug)]
+pub struct InnerProductConfig {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub instance: Column<Instance>,
+    pub q_ip: Selector,
+}
+
+/// Cells laid out by `InnerProductChip::assign`.
+#[derive(Clone, Debug)]
+pub struct InnerProduct<F: FieldExt> {
+    pub a: Vec<AssignedCell<F, F>>,
+    pub b: Vec<AssignedCell<F, F>>,
+    pub out: AssignedCell<F, F>,
+}
+
+pub struct InnerProductChip<F: FieldExt> {
+    config: InnerProductConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> InnerProductChip<F> {
+    pub fn construct(config: InnerProductConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> InnerProductConfig {
+        let [a, b, acc] = advice;
+
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+
+        let q_ip = meta.selector();
+
+        meta.create_gate("inner product", |meta| {
+            let q_ip = meta.query_selector(q_ip);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+
+            vec![q_ip * (acc_cur + a * b - acc_next)]
+        });
+
+        InnerProductConfig {
+            a,
+            b,
+            acc,
+            instance,
+            q_ip,
+        }
+    }
+
+    /// Lays out both private vectors and returns their cells along with `⟨a, b⟩`.
+    /// Vectors of different lengths are a `Error::Synthesis`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &[Value<F>],
+        b: &[Value<F>],
+    ) -> Result<InnerProduct<F>, Error> {
+        if a.len() != b.len() {
+            return Err(Error::Synthesis);
+        }
+
+        let config = &self.config;
+        layouter.assign_region(
+            || "inner product",
+            |mut region| {
+                let mut acc =
+                    region.assign_advice_from_constant(|| "acc", config.acc, 0, F::zero())?;
+                let mut a_cells = Vec::with_capacity(a.len());
+                let mut b_cells = Vec::with_capacity(b.len());
+
+                for (row, (a, b)) in a.iter().zip(b).enumerate() {
+                    config.q_ip.enable(&mut region, row)?;
+
+                    let a = region.assign_advice(|| format!("a_{}", row), config.a, row, || *a)?;
+                    let b = region.assign_advice(|| format!("b_{}", row), config.b, row, || *b)?;
+
+                    let value = acc.value().copied() + a.value().copied() * b.value();
+                    acc = region.assign_advice(|| "acc", config.acc, row + 1, || value)?;
+
+                    a_cells.push(a);
+                    b_cells.push(b);
+                }
+
+                Ok(InnerProduct {
+                    a: a_cells,
+                    b: b_cells,
+                    out: acc,
+                })
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// Proves `⟨a, b⟩ = c` with `c` public.
+    #[derive(Clone, Default)]
+    struct MyCircuit<F> {
+        a: Vec<Value<F>>,
+        b: Vec<Value<F>>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+        type Config = InnerProductConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                a: vec![Value::unknown(); self.a.len()],
+                b: vec![Value::unknown(); self.b.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            let constant = meta.fixed_column();
+
+            InnerProductChip::configure(meta, advice, instance, constant)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = InnerProductChip::construct(config);
+
+            let ip = chip.assign(layouter.namespace(|| "<a, b>"), &self.a, &self.b)?;
+            chip.expose_public(layouter.namespace(|| "c"), &ip.out, 0)
+        }
+    }
+
+    #[test]
+    fn inner_product_4000() {
+        let a: Vec<_> = (0..4000).map(Fp::from).collect();
+        let b: Vec<_> = (0..4000).map(|i| Fp::from(i * i + 1)).collect();
+        let c = inner_product(&a, &b);
+
+        let circuit = MyCircuit {
+            a: a.iter().map(|a| Value::known(*a)).collect(),
+            b: b.iter().map(|b| Value::known(*b)).collect(),
+        };
+        let prover = MockProver::run(12, &circuit, vec![vec![c]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(12, &circuit, vec![vec![c + Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn inner_product_length_mismatch() {
+        let circuit = MyCircuit {
+            a: vec![Value::known(Fp::one()); 3],
+            b: vec![Value::known(Fp::one()); 2],
+        };
+        assert!(matches!(
+            MockProver::run(4, &circuit, vec![vec![Fp::from(2)]]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
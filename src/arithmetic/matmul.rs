@@ -0,0 +1,409 @@
+//! Proves `C = A·B` on top of the example3 `ArithmeticChip`, either for private
+//! matrices by recomputing every entry of `C`, or for public ones with
+//! Freivalds' check `A·(B·r) = C·r` for `r = (1, ρ, ρ^2, ...)`. An `m×n` by
+//! `n×p` product takes `m·p·(2n - 1)` rows in the first mode and about
+//! `2(mn + np + mp)` in the second.
+//!
+//! A prover who knows `ρ` before fixing `C` can pick a `C` that passes, so
+//! `check_freivalds` exposes `A`, `B` and `C` itself and takes `ρ` from the
+//! instance column: the verifier picks it, or hashes it from the public
+//! matrices, once they are known. A wrong `C` then passes with probability at
+//! most `p/|F|`.
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
+
+use super::example3::{ArithmeticChip, ArithmeticConfig};
+
+/// Rows of assigned cells.
+pub type Matrix<F> = Vec<Vec<AssignedCell<F, F>>>;
+
+/// Computes `A·B` natively.
+pub fn matmul<F: FieldExt>(a: &[Vec<F>], b: &[Vec<F>]) -> Vec<Vec<F>> {
+    a.iter()
+        .map(|row| {
+            (0..b[0].len())
+                .map(|j| {
+                    row.iter()
+                        .zip(b)
+                        .fold(F::zero(), |acc, (a, b_row)| acc + *a * b_row[j])
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub struct MatMulChip<F: FieldExt> {
+    chip: ArithmeticChip<F>,
+}
+
+impl<F: FieldExt> MatMulChip<F> {
+    pub fn construct(config: ArithmeticConfig<F>) -> Self {
+        Self {
+            chip: ArithmeticChip::construct(config),
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> ArithmeticConfig<F> {
+        ArithmeticChip::configure(meta, advice, instance, constant)
+    }
+
+    /// Loads a private matrix, given as rows of equal length.
+    pub fn load_matrix(
+        &self,
+        mut layouter: impl Layouter<F>,
+        entries: &[Vec<Value<F>>],
+    ) -> Result<Matrix<F>, Error> {
+        let cols = entries.first().map_or(0, |row| row.len());
+        if cols == 0 || entries.iter().any(|row| row.len() != cols) {
+            return Err(Error::Synthesis);
+        }
+
+        let advice = self.chip.config().advice[0];
+        layouter.assign_region(
+            || "load matrix",
+            |mut region| {
+                let mut offset = 0;
+                entries
+                    .iter()
+                    .enumerate()
+                    .map(|(i, row)| {
+                        row.iter()
+                            .enumerate()
+                            .map(|(j, value)| {
+                                offset += 1;
+                                region.assign_advice(
+                                    || format!("m_{}{}", i, j),
+                                    advice,
+                                    offset - 1,
+                                    || *value,
+                                )
+                            })
+                            .collect()
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Loads the challenge `ρ` of Freivalds' check from the instance column.
+    pub fn load_challenge(
+        &self,
+        layouter: impl Layouter<F>,
+        row: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.chip.load_public(layouter, row)
+    }
+
+    /// Returns `A·B`, recomputing every entry.
+    pub fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Matrix<F>,
+        b: &Matrix<F>,
+    ) -> Result<Matrix<F>, Error> {
+        let (a_cols, b_cols) = match (a.first(), b.first()) {
+            (Some(a_row), Some(b_row)) => (a_row.len(), b_row.len()),
+            _ => return Err(Error::Synthesis),
+        };
+        if a_cols != b.len() {
+            return Err(Error::Synthesis);
+        }
+
+        a.iter()
+            .enumerate()
+            .map(|(i, row)| {
+                (0..b_cols)
+                    .map(|j| {
+                        let col: Vec<_> = b.iter().map(|b_row| &b_row[j]).collect();
+                        self.dot(
+                            layouter.namespace(|| format!("c_{}{}", i, j)),
+                            &row.iter().collect::<Vec<_>>(),
+                            &col,
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Exposes `A`, `B` and `C` row by row from row `first_row` of the
+    /// instance column on, and checks `A·(B·r) = C·r` for `r = (1, ρ, ρ^2, ...)`.
+    ///
+    /// `challenge` must come from the verifier, see the module docs.
+    pub fn check_freivalds(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Matrix<F>,
+        b: &Matrix<F>,
+        c: &Matrix<F>,
+        challenge: &AssignedCell<F, F>,
+        first_row: usize,
+    ) -> Result<(), Error> {
+        let (a_cols, p, c_cols) = match (a.first(), b.first(), c.first()) {
+            (Some(a_row), Some(b_row), Some(c_row)) => (a_row.len(), b_row.len(), c_row.len()),
+            _ => return Err(Error::Synthesis),
+        };
+        let (m, n) = (a.len(), b.len());
+        if a_cols != n || c.len() != m || c_cols != p {
+            return Err(Error::Synthesis);
+        }
+
+        self.expose_matrix(layouter.namespace(|| "expose A"), a, first_row)?;
+        self.expose_matrix(layouter.namespace(|| "expose B"), b, first_row + m * n)?;
+        self.expose_matrix(
+            layouter.namespace(|| "expose C"),
+            c,
+            first_row + m * n + n * p,
+        )?;
+
+        // 1, ρ, ..., ρ^{p-1}, the leading 1 is folded into the dot products
+        let mut powers = vec![challenge.clone()];
+        for k in 2..p {
+            let power = self.chip.mul(
+                layouter.namespace(|| format!("ρ^{}", k)),
+                &powers[k - 2],
+                challenge,
+            )?;
+            powers.push(power);
+        }
+
+        let br: Vec<_> = b
+            .iter()
+            .enumerate()
+            .map(|(k, row)| {
+                self.dot_powers(layouter.namespace(|| format!("(Br)_{}", k)), row, &powers)
+            })
+            .collect::<Result<_, _>>()?;
+
+        for (i, (a_row, c_row)) in a.iter().zip(c).enumerate() {
+            let lhs = self.dot(
+                layouter.namespace(|| format!("(ABr)_{}", i)),
+                &a_row.iter().collect::<Vec<_>>(),
+                &br.iter().collect::<Vec<_>>(),
+            )?;
+            let rhs =
+                self.dot_powers(layouter.namespace(|| format!("(Cr)_{}", i)), c_row, &powers)?;
+
+            layouter.assign_region(
+                || format!("(ABr)_{} = (Cr)_{}", i, i),
+                |mut region| region.constrain_equal(lhs.cell(), rhs.cell()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Exposes the entries of `m` row by row, from row `first_row` of the
+    /// instance column on.
+    pub fn expose_matrix(
+        &self,
+        mut layouter: impl Layouter<F>,
+        m: &Matrix<F>,
+        first_row: usize,
+    ) -> Result<(), Error> {
+        for (offset, cell) in m.iter().flatten().enumerate() {
+            self.chip.expose_public(
+                layouter.namespace(|| format!("entry {}", offset)),
+                cell,
+                first_row + offset,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// `Σ x_k·y_k` with one `mul` and one `add` row per term.
+    fn dot(
+        &self,
+        mut layouter: impl Layouter<F>,
+        xs: &[&AssignedCell<F, F>],
+        ys: &[&AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let mut acc = self
+            .chip
+            .mul(layouter.namespace(|| "x_0·y_0"), xs[0], ys[0])?;
+        for (x, y) in xs.iter().zip(ys).skip(1) {
+            let term = self.chip.mul(layouter.namespace(|| "x·y"), x, y)?;
+            acc = self
+                .chip
+                .add(layouter.namespace(|| "acc + x·y"), &acc, &term)?;
+        }
+        Ok(acc)
+    }
+
+    /// `x_0 + Σ x_k·ρ^k`.
+    fn dot_powers(
+        &self,
+        mut layouter: impl Layouter<F>,
+        xs: &[AssignedCell<F, F>],
+        powers: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let mut acc = xs[0].clone();
+        for (x, power) in xs[1..].iter().zip(powers) {
+            let term = self.chip.mul(layouter.namespace(|| "x·ρ^k"), x, power)?;
+            acc = self
+                .chip
+                .add(layouter.namespace(|| "acc + x·ρ^k"), &acc, &term)?;
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prove::prove_and_verify;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// Which matrices are public, and how `C` is checked.
+    #[derive(Clone, Copy, Default)]
+    enum Mode {
+        /// `C` is recomputed and exposed.
+        #[default]
+        Full,
+        /// `A`, `B` and `C` are exposed after the challenge in row 0.
+        Freivalds,
+    }
+
+    #[derive(Clone, Default)]
+    struct MyCircuit<F> {
+        a: Vec<Vec<Value<F>>>,
+        b: Vec<Vec<Value<F>>>,
+        c: Vec<Vec<Value<F>>>,
+        mode: Mode,
+    }
+
+    fn known<F: FieldExt>(m: &[Vec<F>]) -> Vec<Vec<Value<F>>> {
+        m.iter()
+            .map(|row| row.iter().map(|x| Value::known(*x)).collect())
+            .collect()
+    }
+
+    impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+        type Config = ArithmeticConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            let unknown = |m: &[Vec<Value<F>>]| {
+                m.iter()
+                    .map(|row| vec![Value::unknown(); row.len()])
+                    .collect()
+            };
+            Self {
+                a: unknown(&self.a),
+                b: unknown(&self.b),
+                c: unknown(&self.c),
+                mode: self.mode,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            let constant = meta.fixed_column();
+
+            MatMulChip::configure(meta, advice, instance, constant)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = MatMulChip::construct(config);
+
+            let a = chip.load_matrix(layouter.namespace(|| "A"), &self.a)?;
+            let b = chip.load_matrix(layouter.namespace(|| "B"), &self.b)?;
+
+            match self.mode {
+                Mode::Full => {
+                    let c = chip.mul(layouter.namespace(|| "A·B"), &a, &b)?;
+                    chip.expose_matrix(layouter.namespace(|| "expose C"), &c, 0)
+                }
+                Mode::Freivalds => {
+                    let c = chip.load_matrix(layouter.namespace(|| "C"), &self.c)?;
+                    let challenge = chip.load_challenge(layouter.namespace(|| "ρ"), 0)?;
+                    chip.check_freivalds(
+                        layouter.namespace(|| "A·B = C"),
+                        &a,
+                        &b,
+                        &c,
+                        &challenge,
+                        1,
+                    )
+                }
+            }
+        }
+    }
+
+    fn matrix(rows: usize, cols: usize, seed: u64) -> Vec<Vec<Fp>> {
+        (0..rows)
+            .map(|i| {
+                (0..cols)
+                    .map(|j| Fp::from(seed + (i * cols + j) as u64 * 7 % 11))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matmul_full() {
+        let (a, b) = (matrix(3, 4, 1), matrix(4, 2, 5));
+        let c = matmul(&a, &b);
+
+        let circuit = MyCircuit {
+            a: known(&a),
+            b: known(&b),
+            ..Default::default()
+        };
+
+        let mut public_inputs: Vec<_> = c.concat();
+        let prover = MockProver::run(7, &circuit, vec![public_inputs.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        public_inputs[3] += Fp::one();
+        let prover = MockProver::run(7, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn matmul_freivalds() {
+        let (a, b) = (matrix(3, 4, 1), matrix(4, 2, 5));
+        let mut c = matmul(&a, &b);
+        // stands in for a challenge drawn after A, B and C are published
+        let challenge = Fp::from(0x1234_5678);
+
+        let public_inputs = |c: &[Vec<Fp>]| -> Vec<_> {
+            std::iter::once(challenge)
+                .chain(a.concat())
+                .chain(b.concat())
+                .chain(c.concat())
+                .collect()
+        };
+
+        let circuit = MyCircuit {
+            a: known(&a),
+            b: known(&b),
+            c: known(&c),
+            mode: Mode::Freivalds,
+        };
+        let prover = MockProver::run(7, &circuit, vec![public_inputs(&c)]).unwrap();
+        prover.assert_satisfied();
+        assert!(prove_and_verify(7, circuit.clone(), &[&public_inputs(&c)]).is_ok());
+
+        // a published C that is not A·B
+        c[2][1] += Fp::one();
+        let circuit = MyCircuit {
+            c: known(&c),
+            ..circuit
+        };
+        // MockProver cannot report failures in a circuit with row-less regions
+        // such as the `(ABr)_i = (Cr)_i` ones, so this goes through a real proof
+        let public_inputs = public_inputs(&c);
+        assert!(prove_and_verify(7, circuit, &[&public_inputs]).is_err());
+    }
+}
@@ -1,30 +1,36 @@
-use gadget::is_zero::{IsZeroChip, IsZeroConfig};
-
-use halo2_proofs::{
-    arithmetic::FieldExt, circuit::*, dev::MockProver, pasta::Fp, plonk::*, poly::Rotation
+use gadget::{
+    is_zero::{IsZeroChip, IsZeroConfig},
+    select::{SelectChip, SelectConfig},
 };
 
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
 
-#[derive(Debug,Clone)]
-struct FunctionConfig<F: FieldExt> {
+/// `f(a, b, c) = if a == b {c} else {a-b}`, with the comparison on one row
+///
+///   a | b | a - b | a == b
+///
+/// and the branch taken by a `SelectChip`.
+#[derive(Debug, Clone)]
+pub struct FunctionConfig<F: FieldExt> {
     selector: Selector,
     a: Column<Advice>,
     b: Column<Advice>,
     c: Column<Advice>,
     output: Column<Advice>,
     a_equals_b: IsZeroConfig<F>,
+    select: SelectConfig,
 }
 
-struct FunctionChip<F: FieldExt> {
+pub struct FunctionChip<F: FieldExt> {
     config: FunctionConfig<F>,
 }
 
-impl <F: FieldExt> FunctionChip<F> {
-    pub fn construct (config: FunctionConfig<F>) -> Self {
-        FunctionChip {config}
+impl<F: FieldExt> FunctionChip<F> {
+    pub fn construct(config: FunctionConfig<F>) -> Self {
+        FunctionChip { config }
     }
 
-    pub fn configure(meta: &mut ConstraintSystem<F>) -> FunctionConfig<F>{
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> FunctionConfig<F> {
         let selector = meta.selector();
         let a = meta.advice_column();
         let b = meta.advice_column();
@@ -33,33 +39,32 @@ impl <F: FieldExt> FunctionChip<F> {
 
         let is_zero_advice_column = meta.advice_column();
         let a_equals_b = IsZeroChip::configure(
-                      meta, 
-                      |meta| meta.query_selector(selector), 
-                      |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()), 
-                      is_zero_advice_column,
-                    );
+            meta,
+            |meta| meta.query_selector(selector),
+            |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
+            is_zero_advice_column,
+        );
 
-        meta.create_gate("f(a, b, c) = if a == b {c} else {a-b}", |meta|{
+        meta.create_gate("a - b, a == b", |meta| {
             let s = meta.query_selector(selector);
             let a = meta.query_advice(a, Rotation::cur());
             let b = meta.query_advice(b, Rotation::cur());
-            let c = meta.query_advice(c, Rotation::cur());
-            let output = meta.query_advice(output, Rotation::cur());
-    
-        vec![
-            s.clone() * (a_equals_b.expr() * (output.clone() - c)),
-            s * (Expression::Constant(F::one())- a_equals_b.expr()) * (output - (a-b))
-        ]
-
-    });       
-
-    FunctionConfig {
-        selector,
-        a,
-        b,
-        c,
-        output,
-        a_equals_b,
+            let diff = meta.query_advice(c, Rotation::cur());
+            let flag = meta.query_advice(output, Rotation::cur());
+
+            vec![s.clone() * (diff - (a - b)), s * (flag - a_equals_b.expr())]
+        });
+
+        let select = SelectChip::configure(meta, [a, b, c, output]);
+
+        FunctionConfig {
+            selector,
+            a,
+            b,
+            c,
+            output,
+            a_equals_b,
+            select,
         }
     }
 
@@ -69,37 +74,46 @@ impl <F: FieldExt> FunctionChip<F> {
         a: F,
         b: F,
         c: F,
-    ) -> Result<(), Error> {
-
-        let is_zero_chip = IsZeroChip::construct(self.config.a_equals_b.clone());
-
-        let _ = layouter.assign_region(
-            || "f(a,b,c) = if a == b {c} else {a-b}",
-            |mut region| Ok({
-                self.config.selector.enable(&mut region, 0)?;
-                region.assign_advice(|| "a", self.config.a, 0, || Value::known(a))?;
-                region.assign_advice(|| "b", self.config.b, 0, || Value::known(b))?;
-                region.assign_advice(|| "c", self.config.c, 0, || Value::known(c))?;
-
-                is_zero_chip.assign(&mut region, 0, Value::known(a-b))?;
-
-                let output = if a == b {c} else { a-b };
-                region.assign_advice(|| "output", self.config.output, 0, || Value::known(output))?;
-        }),
-     );
-
-     Ok(())
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let is_zero_chip = IsZeroChip::construct(config.a_equals_b.clone());
+        let select_chip = SelectChip::construct(config.select.clone());
+
+        let (diff, flag, c) = layouter.assign_region(
+            || "a - b, a == b",
+            |mut region| {
+                config.selector.enable(&mut region, 0)?;
+                region.assign_advice(|| "a", config.a, 0, || Value::known(a))?;
+                region.assign_advice(|| "b", config.b, 0, || Value::known(b))?;
+                let diff = region.assign_advice(|| "a - b", config.c, 0, || Value::known(a - b))?;
+                is_zero_chip.assign(&mut region, 0, Value::known(a - b))?;
+
+                let flag = F::from((a == b) as u64);
+                let flag =
+                    region.assign_advice(|| "a == b", config.output, 0, || Value::known(flag))?;
+                let c = region.assign_advice(|| "c", config.c, 1, || Value::known(c))?;
+
+                Ok((diff, flag, c))
+            },
+        )?;
+
+        select_chip.select(
+            layouter.namespace(|| "if a == b {c} else {a-b}"),
+            &flag,
+            &c,
+            &diff,
+        )
     }
 }
 
 #[derive(Default)]
-struct FunctionCircuit<F> {
-    a: F,
-    b: F,
-    c: F,
+pub struct FunctionCircuit<F> {
+    pub a: F,
+    pub b: F,
+    pub c: F,
 }
 
-impl<F: FieldExt> Circuit<F> for FunctionCircuit<F>  {
+impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
     type Config = FunctionConfig<F>;
     type FloorPlanner = SimpleFloorPlanner;
 
@@ -111,7 +125,6 @@ impl<F: FieldExt> Circuit<F> for FunctionCircuit<F>  {
         FunctionChip::configure(meta)
     }
 
-  
     fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
         let chip = FunctionChip::construct(config);
         chip.assign(layouter, self.a, self.b, self.c)?;
@@ -120,39 +133,38 @@ impl<F: FieldExt> Circuit<F> for FunctionCircuit<F>  {
     }
 }
 
-fn main() {
-    let circuit = FunctionCircuit {
-        a: Fp::from(10),
-        b: Fp::from(20),
-        c: Fp::from(15),
-    };
-    let prover = MockProver::run(4, &circuit, vec![]).unwrap();
-    prover.assert_satisfied();
-
-    println!("Hello World");
-
-}
-
-
 #[cfg(test)]
 mod tests {
-    use halo2_proofs::pasta::Fp;
     use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
 
     #[test]
     fn iszero_example() {
         let k = 4;
 
-        let circuit = FunctionCircuit {
-            a: Fp::from(10),
-            b: Fp::from(20),
-            c: Fp::from(15),
-        };
-        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
-        prover.assert_satisfied();
-    
-        println!("Hello World");
-    
+        for (a, b) in [(10, 20), (15, 15)] {
+            let circuit = FunctionCircuit {
+                a: Fp::from(a),
+                b: Fp::from(b),
+                c: Fp::from(15),
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn iszero_example_real_proof() {
+        let k = 4;
+
+        for (a, b) in [(10, 20), (15, 15)] {
+            let circuit = FunctionCircuit {
+                a: Fp::from(a),
+                b: Fp::from(b),
+                c: Fp::from(15),
+            };
+            assert!(crate::prove::prove_and_verify(k, circuit, &[]).is_ok());
+        }
     }
 
     #[cfg(feature = "dev-graph")]
@@ -162,7 +174,9 @@ mod tests {
 
         let root = BitMapBackend::new("iszero -1-layout.png", (1024, 3096)).into_drawing_area();
         root.fill(&WHITE).unwrap();
-        let root = root.titled("isZero-Function 1 Layout", ("sans-serif", 60)).unwrap();
+        let root = root
+            .titled("isZero-Function 1 Layout", ("sans-serif", 60))
+            .unwrap();
 
         let circuit = FunctionCircuit {
             a: Fp::from(10),
@@ -173,4 +187,4 @@ mod tests {
             .render(4, &circuit, &root)
             .unwrap();
     }
-}
\ No newline at end of file
+}
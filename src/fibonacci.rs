@@ -0,0 +1,25 @@
+mod batch;
+mod chip;
+mod circuit;
+pub mod example1;
+pub mod example2;
+pub mod example3;
+pub mod example4;
+mod fast_doubling;
+mod overflow;
+mod private_index;
+mod recurrence;
+mod trace;
+
+pub use batch::{BatchFibonacciCircuit, ColumnLayout, LayoutChoice, RowLayout};
+pub use chip::{FibonacciChip, FibonacciConfig, FibonacciInstructions, FibonacciLayout};
+pub use circuit::{fibonacci, FibonacciCircuit};
+pub use fast_doubling::{
+    fast_fibonacci, FastDoublingChip, FastDoublingConfig, FastFibonacciCircuit,
+};
+pub use overflow::{
+    CheckedFibonacciChip, CheckedFibonacciCircuit, CheckedFibonacciConfig, LIMB_BITS, LIMB_RANGE,
+};
+pub use private_index::{PrivateIndexChip, PrivateIndexCircuit, PrivateIndexConfig};
+pub use recurrence::{linear_recurrence, RecurrenceChip, RecurrenceConfig};
+pub use trace::{iterate, TraceChip, TraceConfig, TraceLayout};
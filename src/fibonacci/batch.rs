@@ -0,0 +1,199 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
+
+use super::{fibonacci, FibonacciChip, FibonacciConfig, FibonacciInstructions, FibonacciLayout};
+
+/// Picks the `FibonacciLayout` of a circuit, since `Circuit::configure` has no
+/// access to the circuit itself.
+pub trait LayoutChoice {
+    fn layout<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> FibonacciLayout;
+
+    /// Rows taken by one sequence of `nrows` terms.
+    fn rows(nrows: usize) -> usize;
+}
+
+/// `FibonacciLayout::RowPerStep`, as in example1 / example4.
+#[derive(Clone, Debug, Default)]
+pub struct RowLayout;
+
+/// `FibonacciLayout::SingleColumn`, as in example2 / example3.
+#[derive(Clone, Debug, Default)]
+pub struct ColumnLayout;
+
+impl LayoutChoice for RowLayout {
+    fn layout<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> FibonacciLayout {
+        FibonacciLayout::RowPerStep([
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ])
+    }
+
+    fn rows(nrows: usize) -> usize {
+        // the seeds share the first row
+        std::cmp::max(nrows.saturating_sub(2), 1)
+    }
+}
+
+impl LayoutChoice for ColumnLayout {
+    fn layout<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> FibonacciLayout {
+        FibonacciLayout::SingleColumn(meta.advice_column())
+    }
+
+    fn rows(nrows: usize) -> usize {
+        std::cmp::max(nrows, 2)
+    }
+}
+
+/// Proves `F(n)` for many seed pairs at once, one region per sequence. Every
+/// sequence `i` takes three consecutive rows of the instance column:
+///
+///  instance
+///    a_i        row 3i
+///    b_i        row 3i + 1
+///    F_i(n)     row 3i + 2
+#[derive(Clone, Debug)]
+pub struct BatchFibonacciCircuit<F, L> {
+    pub seeds: Vec<(Value<F>, Value<F>)>,
+    pub n: usize,
+    _marker: PhantomData<L>,
+}
+
+impl<F: FieldExt, L: LayoutChoice> BatchFibonacciCircuit<F, L> {
+    pub fn new(seeds: &[(F, F)], n: usize) -> Self {
+        Self {
+            seeds: seeds
+                .iter()
+                .map(|(a, b)| (Value::known(*a), Value::known(*b)))
+                .collect(),
+            n,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Smallest `k` whose usable rows fit every sequence and the public inputs.
+    pub fn k(&self) -> u32 {
+        let mut meta = ConstraintSystem::<F>::default();
+        Self::configure(&mut meta);
+
+        let rows = std::cmp::max(self.seeds.len() * L::rows(self.n + 1), self.seeds.len() * 3);
+        let rows = std::cmp::max(rows + meta.blinding_factors() + 1, meta.minimum_rows());
+        rows.next_power_of_two().trailing_zeros()
+    }
+
+    /// The expected public inputs, `[a_i, b_i, F_i(n)]` for every seed pair.
+    pub fn public_inputs(seeds: &[(F, F)], n: usize) -> Vec<F> {
+        seeds
+            .iter()
+            .flat_map(|&(a, b)| [a, b, fibonacci(a, b, n)])
+            .collect()
+    }
+}
+
+impl<F: FieldExt, L: LayoutChoice> Circuit<F> for BatchFibonacciCircuit<F, L> {
+    type Config = FibonacciConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            seeds: vec![(Value::unknown(), Value::unknown()); self.seeds.len()],
+            n: self.n,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let layout = L::layout(meta);
+        let instance = meta.instance_column();
+
+        FibonacciChip::configure(meta, layout, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FibonacciChip::construct(config);
+
+        for (i, (a, b)) in self.seeds.iter().enumerate() {
+            let (a_cell, b_cell, out_cell) = chip.assign_with_seeds(
+                layouter.namespace(|| format!("sequence {}", i)),
+                *a,
+                *b,
+                self.n + 1,
+            )?;
+
+            chip.expose_public(layouter.namespace(|| format!("a_{}", i)), a_cell, 3 * i)?;
+            chip.expose_public(layouter.namespace(|| format!("b_{}", i)), b_cell, 3 * i + 1)?;
+            chip.expose_public(
+                layouter.namespace(|| format!("out_{}", i)),
+                out_cell,
+                3 * i + 2,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    fn seeds(len: u64) -> Vec<(Fp, Fp)> {
+        (0..len)
+            .map(|i| (Fp::from(i), Fp::from(i * i + 1)))
+            .collect()
+    }
+
+    fn run<L: LayoutChoice>() {
+        let seeds = seeds(100);
+        let circuit = BatchFibonacciCircuit::<Fp, L>::new(&seeds, 9);
+
+        let mut public_input = BatchFibonacciCircuit::<Fp, L>::public_inputs(&seeds, 9);
+        assert_eq!(public_input[2], Fp::from(34)); // seeds (0, 1)
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        // a single wrong output anywhere in the batch fails the whole proof
+        public_input[3 * 57 + 2] += Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// `F(0)` and `F(1)` are exposed straight from the seeds.
+    fn run_seeds_only<L: LayoutChoice>() {
+        let seeds = seeds(10);
+
+        for n in 0..2 {
+            let circuit = BatchFibonacciCircuit::<Fp, L>::new(&seeds, n);
+
+            let mut public_input = BatchFibonacciCircuit::<Fp, L>::public_inputs(&seeds, n);
+            assert_eq!(public_input[3 * 7 + 2], public_input[3 * 7 + n]);
+
+            let prover =
+                MockProver::run(circuit.k(), &circuit, vec![public_input.clone()]).unwrap();
+            prover.assert_satisfied();
+
+            public_input[3 * 7 + 2] += Fp::one();
+            let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn batch_row_layout() {
+        run::<RowLayout>();
+        run_seeds_only::<RowLayout>();
+    }
+
+    #[test]
+    fn batch_column_layout() {
+        run::<ColumnLayout>();
+        run_seeds_only::<ColumnLayout>();
+    }
+}
@@ -0,0 +1,440 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+pub trait FibonacciInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a term of the sequence.
+    type Num;
+
+    /// Loads the two seeds of the sequence into the circuit as private inputs.
+    fn load_seeds(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<(Self::Num, Self::Num), Error>;
+
+    /// Returns `c = a + b`, the term following the consecutive terms `a` and `b`.
+    fn step(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Exposes a term as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+
+/// How the Fibonacci table is laid out in the main table.
+#[derive(Debug, Clone, Copy)]
+pub enum FibonacciLayout {
+    /// Three advice columns, one row per step (example1 / example4):
+    ///
+    ///  col_a | col_b | col_c | selector
+    ///    a       b       c       s
+    RowPerStep([Column<Advice>; 3]),
+    /// A single advice column, the gate looks two rows ahead (example2 / example3):
+    ///
+    ///  advice | selector
+    ///    a    |   s
+    ///    b    |
+    ///    c    |
+    SingleColumn(Column<Advice>),
+}
+
+#[derive(Debug, Clone)]
+pub struct FibonacciConfig {
+    pub layout: FibonacciLayout,
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FibonacciChip<F: FieldExt> {
+    config: FibonacciConfig,
+    _marker: PhantomData<F>,
+}
+
+/// Where the two seeds of the sequence come from.
+enum Seed<F> {
+    Instance(usize),
+    Private(Value<F>),
+}
+
+impl<F: FieldExt> FibonacciChip<F> {
+    pub fn construct(config: FibonacciConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        layout: FibonacciLayout,
+        instance: Column<Instance>,
+    ) -> FibonacciConfig {
+        let selector = meta.selector();
+
+        // enable equality to check the permutation on the specified columns
+        meta.enable_equality(instance);
+
+        match layout {
+            FibonacciLayout::RowPerStep([col_a, col_b, col_c]) => {
+                meta.enable_equality(col_a);
+                meta.enable_equality(col_b);
+                meta.enable_equality(col_c);
+
+                meta.create_gate("add", |meta| {
+                    let s = meta.query_selector(selector);
+                    let a = meta.query_advice(col_a, Rotation::cur());
+                    let b = meta.query_advice(col_b, Rotation::cur());
+                    let c = meta.query_advice(col_c, Rotation::cur());
+                    vec![s * (a + b - c)]
+                });
+            }
+            FibonacciLayout::SingleColumn(advice) => {
+                meta.enable_equality(advice);
+
+                meta.create_gate("add", |meta| {
+                    let s = meta.query_selector(selector);
+                    let a = meta.query_advice(advice, Rotation::cur());
+                    let b = meta.query_advice(advice, Rotation::next());
+                    let c = meta.query_advice(advice, Rotation(2));
+                    vec![s * (a + b - c)]
+                });
+            }
+        }
+
+        FibonacciConfig {
+            layout,
+            selector,
+            instance,
+        }
+    }
+
+    /// Lays out the first `nrows` terms of the sequence, seeded from rows 0 and 1
+    /// of the instance column, and returns the cell holding the last term. `nrows`
+    /// must be at least 1.
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        nrows: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let (_, _, out) =
+            self.assign_sequence(layouter, Seed::Instance(0), Seed::Instance(1), nrows)?;
+        Ok(out)
+    }
+
+    /// Lays out the first `nrows` terms of the sequence from two private seeds.
+    /// Returns the cells holding both seeds and the last term.
+    #[allow(clippy::type_complexity)]
+    pub fn assign_with_seeds(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        nrows: usize,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        self.assign_sequence(layouter, Seed::Private(a), Seed::Private(b), nrows)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn assign_sequence(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Seed<F>,
+        b: Seed<F>,
+        nrows: usize,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        if nrows == 0 {
+            return Err(Error::Synthesis);
+        }
+        // F(0) and F(1) are the seeds themselves, no step is laid out
+        if nrows < 3 {
+            let (a_cell, b_cell) = self.assign_seeds(layouter, &a, &b)?;
+            let out = if nrows == 1 { &a_cell } else { &b_cell }.clone();
+            return Ok((a_cell, b_cell, out));
+        }
+
+        match self.config.layout {
+            FibonacciLayout::RowPerStep(advice) => {
+                let (a_cell, mut prev_b, mut prev_c) = layouter.assign_region(
+                    || "first row",
+                    |mut region| {
+                        self.config.selector.enable(&mut region, 0)?;
+
+                        let a_cell = self.assign_seed(&mut region, "f(0)", &a, advice[0], 0)?;
+                        let b_cell = self.assign_seed(&mut region, "f(1)", &b, advice[1], 0)?;
+                        let c_cell = region.assign_advice(
+                            || "c",
+                            advice[2],
+                            0,
+                            || a_cell.value().copied() + b_cell.value(),
+                        )?;
+
+                        Ok((a_cell, b_cell, c_cell))
+                    },
+                )?;
+                let b_cell = prev_b.clone();
+
+                for _ in 3..nrows {
+                    let c_cell = layouter.assign_region(
+                        || "next row",
+                        |mut region| {
+                            self.config.selector.enable(&mut region, 0)?;
+
+                            // Copy b & c of the previous row to a & b of the current row
+                            prev_b.copy_advice(|| "a", &mut region, advice[0], 0)?;
+                            prev_c.copy_advice(|| "b", &mut region, advice[1], 0)?;
+
+                            region.assign_advice(
+                                || "c",
+                                advice[2],
+                                0,
+                                || prev_b.value().copied() + prev_c.value(),
+                            )
+                        },
+                    )?;
+                    prev_b = prev_c;
+                    prev_c = c_cell;
+                }
+
+                Ok((a_cell, b_cell, prev_c))
+            }
+            FibonacciLayout::SingleColumn(advice) => layouter.assign_region(
+                || "entire fibonacci table",
+                |mut region| {
+                    let a_cell = self.assign_seed(&mut region, "f(0)", &a, advice, 0)?;
+                    let b_cell = self.assign_seed(&mut region, "f(1)", &b, advice, 1)?;
+
+                    let mut prev_a = a_cell.clone();
+                    let mut prev_b = b_cell.clone();
+                    for row in 2..nrows {
+                        self.config.selector.enable(&mut region, row - 2)?;
+
+                        let c_cell = region.assign_advice(
+                            || "advice",
+                            advice,
+                            row,
+                            || prev_a.value().copied() + prev_b.value(),
+                        )?;
+                        prev_a = prev_b;
+                        prev_b = c_cell;
+                    }
+
+                    Ok((a_cell, b_cell, prev_b))
+                },
+            ),
+        }
+    }
+
+    /// Lays out the seeds where the gate expects `a` and `b`, with no gate enabled.
+    #[allow(clippy::type_complexity)]
+    fn assign_seeds(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Seed<F>,
+        b: &Seed<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let (col_a, col_b, b_offset) = match self.config.layout {
+            FibonacciLayout::RowPerStep(advice) => (advice[0], advice[1], 0),
+            FibonacciLayout::SingleColumn(advice) => (advice, advice, 1),
+        };
+
+        layouter.assign_region(
+            || "load seeds",
+            |mut region| {
+                let a_cell = self.assign_seed(&mut region, "a", a, col_a, 0)?;
+                let b_cell = self.assign_seed(&mut region, "b", b, col_b, b_offset)?;
+                Ok((a_cell, b_cell))
+            },
+        )
+    }
+
+    fn assign_seed(
+        &self,
+        region: &mut Region<'_, F>,
+        name: &str,
+        seed: &Seed<F>,
+        column: Column<Advice>,
+        offset: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        match seed {
+            Seed::Instance(row) => region.assign_advice_from_instance(
+                || name,
+                self.config.instance,
+                *row,
+                column,
+                offset,
+            ),
+            Seed::Private(value) => region.assign_advice(|| name, column, offset, || *value),
+        }
+    }
+}
+
+impl<F: FieldExt> Chip<F> for FibonacciChip<F> {
+    type Config = FibonacciConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> FibonacciInstructions<F> for FibonacciChip<F> {
+    type Num = AssignedCell<F, F>;
+
+    fn load_seeds(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        self.assign_seeds(layouter, &Seed::Private(a), &Seed::Private(b))
+    }
+
+    fn step(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let (col_a, col_b, col_c, b_offset, c_offset) = match self.config.layout {
+            FibonacciLayout::RowPerStep(advice) => (advice[0], advice[1], advice[2], 0, 0),
+            FibonacciLayout::SingleColumn(advice) => (advice, advice, advice, 1, 2),
+        };
+
+        layouter.assign_region(
+            || "step",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+
+                a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                b.copy_advice(|| "b", &mut region, col_b, b_offset)?;
+
+                region.assign_advice(|| "c", col_c, c_offset, || a.value().copied() + b.value())
+            },
+        )
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(num.cell(), self.config.instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::{RecurrenceChip, RecurrenceConfig};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// Any chip with `FibonacciInstructions` can drive the sequence.
+    fn nth<F: FieldExt, I: FibonacciInstructions<F>>(
+        chip: &I,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        n: usize,
+    ) -> Result<I::Num, Error>
+    where
+        I::Num: Clone,
+    {
+        let (mut a, mut b) = chip.load_seeds(layouter.namespace(|| "seeds"), a, b)?;
+        for _ in 1..n {
+            let c = chip.step(layouter.namespace(|| "step"), a, b.clone())?;
+            a = b;
+            b = c;
+        }
+        Ok(b)
+    }
+
+    /// A Lucas chip and a Fibonacci chip sharing one advice column and the
+    /// instance column: `[L(0), L(1), L(n), F(n)]`.
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        a: Value<F>,
+        b: Value<F>,
+        single_column: bool,
+    }
+
+    impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+        type Config = (FibonacciConfig, FibonacciConfig, RecurrenceConfig<2>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                single_column: self.single_column,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let coeffs = [meta.fixed_column(), meta.fixed_column()];
+            let instance = meta.instance_column();
+
+            (
+                FibonacciChip::configure(meta, FibonacciLayout::RowPerStep(advice), instance),
+                FibonacciChip::configure(meta, FibonacciLayout::SingleColumn(advice[0]), instance),
+                RecurrenceChip::configure(meta, advice[0], coeffs, instance),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (rows, column, lucas): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let lucas = RecurrenceChip::construct(lucas);
+            let out = lucas.assign(layouter.namespace(|| "lucas"), [F::one(), F::one()], 10)?;
+            lucas.expose_public(layouter.namespace(|| "lucas out"), &out, 2)?;
+
+            let chip = FibonacciChip::construct(if self.single_column { column } else { rows });
+            let out = nth(&chip, layouter.namespace(|| "fibonacci"), self.a, self.b, 9)?;
+            chip.expose_public(layouter.namespace(|| "fibonacci out"), out, 3)
+        }
+    }
+
+    #[test]
+    fn fibonacci_instructions() {
+        let k = 6;
+        let (a, b) = (Fp::one(), Fp::one());
+
+        for single_column in [false, true] {
+            let circuit = MyCircuit {
+                a: Value::known(a),
+                b: Value::known(b),
+                single_column,
+            };
+
+            let mut public_input = vec![Fp::from(2), Fp::from(1), Fp::from(76), Fp::from(55)];
+
+            let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
+            prover.assert_satisfied();
+
+            public_input[3] += Fp::one();
+            let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+}
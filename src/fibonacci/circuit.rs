@@ -0,0 +1,144 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
+
+use super::{FibonacciChip, FibonacciConfig, FibonacciInstructions, FibonacciLayout};
+
+/// Computes `F(n)` natively for the sequence seeded with `F(0) = a` and `F(1) = b`.
+pub fn fibonacci<F: FieldExt>(a: F, b: F, n: usize) -> F {
+    (0..n).fold((a, b), |(a, b), _| (b, a + b)).0
+}
+
+/// Proves `F(n)` for a runtime-chosen number of steps `n`.
+///
+/// The seeds and the output are public, in this order:
+///
+///  instance
+///    F(0)
+///    F(1)
+///    F(n)
+#[derive(Clone, Debug)]
+pub struct FibonacciCircuit<F> {
+    pub n: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FibonacciCircuit<F> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Number of rows used by the Fibonacci table, `F(0)..=F(n)`.
+    pub fn rows(&self) -> usize {
+        self.n + 1
+    }
+
+    /// Smallest `k` whose usable rows fit the table and the three public inputs,
+    /// after the blinding rows.
+    pub fn k(&self) -> u32 {
+        let mut meta = ConstraintSystem::<F>::default();
+        Self::configure(&mut meta);
+
+        let rows = std::cmp::max(self.rows(), 3);
+        let rows = std::cmp::max(rows + meta.blinding_factors() + 1, meta.minimum_rows());
+        rows.next_power_of_two().trailing_zeros()
+    }
+
+    /// The expected public inputs `[F(0), F(1), F(n)]`.
+    pub fn public_inputs(&self, a: F, b: F) -> Vec<F> {
+        vec![a, b, fibonacci(a, b, self.n)]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FibonacciCircuit<F> {
+    type Config = FibonacciConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // the seeds live in the instance column, only the shape is kept
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = meta.advice_column();
+        let instance = meta.instance_column();
+
+        FibonacciChip::configure(meta, FibonacciLayout::SingleColumn(advice), instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FibonacciChip::construct(config);
+
+        let out_cell = chip.assign(layouter.namespace(|| "entire table"), self.rows())?;
+
+        chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fibonacci, FibonacciCircuit};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    #[test]
+    fn fibonacci_native() {
+        let one = Fp::one();
+        assert_eq!(fibonacci(one, one, 9), Fp::from(55));
+        assert_eq!(fibonacci(Fp::zero(), one, 93), Fp::from(12200160415121876738));
+    }
+
+    #[test]
+    fn fibonacci_n_steps() {
+        let circuit = FibonacciCircuit::<Fp>::new(9);
+        assert_eq!(circuit.k(), 4);
+
+        let public_input = circuit.public_inputs(Fp::one(), Fp::one());
+        assert_eq!(public_input[2], Fp::from(55));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn fibonacci_seeds_only() {
+        let (a, b) = (Fp::from(3), Fp::from(5));
+
+        for n in 0..3 {
+            let circuit = FibonacciCircuit::<Fp>::new(n);
+
+            let mut public_input = circuit.public_inputs(a, b);
+            assert_eq!(public_input[2], [a, b, a + b][n]);
+
+            let prover =
+                MockProver::run(circuit.k(), &circuit, vec![public_input.clone()]).unwrap();
+            prover.assert_satisfied();
+
+            public_input[2] += Fp::one();
+            let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn fibonacci_1000() {
+        let circuit = FibonacciCircuit::<Fp>::new(1000);
+
+        let mut public_input = circuit.public_inputs(Fp::zero(), Fp::one());
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        public_input[2] += Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -1,147 +1,16 @@
-use std::marker::PhantomData;
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
 
-use halo2_proofs::{
-    arithmetic::FieldExt, circuit::*, dev::MockProver, pasta::Fp, plonk::*, poly::Rotation
-};
-
-#[derive(Debug, Clone)]
-struct ACell<F: FieldExt>(AssignedCell<F, F>);
-
-#[derive(Debug, Clone)]
-struct FiboConfig {
-    pub advice: [Column<Advice>; 3],
-    pub selector: Selector,
-    pub instance: Column<Instance>,
-}
-
-struct FiboChip<F: FieldExt> {
-    config: FiboConfig,
-    _marker: PhantomData<F>
-}
-
-impl<F: FieldExt> FiboChip<F>  {
-    fn construct(config: FiboConfig) -> Self{
-        Self {config, _marker: PhantomData}
-    }  
-
-    // Define columns or custom gates
-    fn configure(
-        meta: &mut ConstraintSystem<F>, 
-        advice: [Column<Advice>; 3],
-        instance: Column<Instance>
-    ) -> FiboConfig {
-        let col_a = advice[0];
-        let col_b = advice[1];
-        let col_c = advice[2];
-
-        let selector = meta.selector();
-        
-        // enable equality to check the permutation on the specified columns
-        meta.enable_equality(col_a);
-        meta.enable_equality(col_b);
-        meta.enable_equality(col_c);
-        meta.enable_equality(instance);
-
-        // Define our add gate
-        meta.create_gate("add", |meta| {
-            //
-            //  col_a | col_b | col_c | selector
-            //     a       b       c       s
-            let s = meta.query_selector(selector);
-            let a = meta.query_advice(col_a, Rotation::cur());
-            let b = meta.query_advice(col_b, Rotation::cur());
-            let c = meta.query_advice(col_c, Rotation::cur());
-            vec![s*(a+b-c)] // constraint to be return by the custom gate "add"
-        });
-
-        FiboConfig {
-            advice: [col_a,col_b, col_c],
-            selector,
-            instance,
-        }
-    } 
-
-    fn assign_first_row(&self, mut layouter: impl  Layouter<F>, a: Option<F>, b: Option<F>) 
-            ->  Result<(ACell<F>, ACell<F>, ACell<F>), Error> {
-        layouter.assign_region(|| "first_row", 
-        |mut region| {
-            self.config.selector.enable(&mut region, 0)?;
-            let a_cell = region.assign_advice(
-                || "a",
-                self.config.advice[0], 
-                0,
-                || a.ok_or(Error::Synthesis),
-            ).map(ACell)?;
-
-            let b_cell = region.assign_advice(
-                || "b",
-                self.config.advice[1], 
-                0,
-                || b.ok_or(Error::Synthesis),
-            ).map(ACell)?;
-
-            let c_val = a.and_then(|a| b.map(|b| a+b));
-
-            let c_cell = region.assign_advice(
-                || "c",
-                self.config.advice[2], 
-                0,
-                || c_val.ok_or(Error::Synthesis),
-            ).map(ACell)?;
-
-           Ok((a_cell, b_cell, c_cell))     
-        })
-    }
-
-    fn assign_row(&self, mut layouter: impl Layouter<F>,  prev_b: &ACell<F>, prev_c: &ACell<F>)
-    -> Result<ACell<F>, Error> {
-        layouter.assign_region(|| "next_row",
-            |mut region| {
-                self.config.selector.enable(&mut region, 0)?;
-
-                prev_b.0.copy_advice(|| "a", &mut region, self.config.advice[0], 0)?;
-                prev_c.0.copy_advice(|| "b", &mut region, self.config.advice[1], 0)?;
-
-                let c_val = prev_b.0.value().and_then(
-                    |b|{
-                        prev_c.0.value().map(|c| *b + *c)
-                    }
-                );
-
-                let c_cell = region.assign_advice(
-                    || "c",
-                    self.config.advice[2], 
-                    0,
-                    || c_val.ok_or(Error::Synthesis),
-                ).map(ACell)?;
-    
-                Ok(c_cell)
-            }
-
-        )
-    }
-
-    pub fn expose_public(
-        &self,
-        mut layouter: impl  Layouter<F>,
-        cell: &ACell<F>,
-        row: usize,
-    )-> Result<(), Error>{
-        layouter.constrain_instance(cell.0.cell(), self.config.instance, row)
-
-    }
- 
-}
+use super::{FibonacciChip, FibonacciConfig, FibonacciInstructions, FibonacciLayout};
 
+/// Row-per-step layout with private seeds, exposed as public inputs.
 #[derive(Default)]
-struct MyCircuit<F> {
-    pub a: Option<F>,
-    pub b: Option<F>,
+pub struct MyCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
 }
 
-impl <F: FieldExt> Circuit<F> for MyCircuit<F> {
-    type Config = FiboConfig;
-
+impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+    type Config = FibonacciConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
@@ -154,69 +23,35 @@ impl <F: FieldExt> Circuit<F> for MyCircuit<F> {
         let col_c = meta.advice_column();
         let instance = meta.instance_column();
 
-        FiboChip::configure(meta, [col_a, col_b, col_c], instance)
+        FibonacciChip::configure(
+            meta,
+            FibonacciLayout::RowPerStep([col_a, col_b, col_c]),
+            instance,
+        )
     }
 
-    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
-        let chip = FiboChip::construct(config);
-
-        let (_prev_a, mut prev_b, mut prev_c) = chip.assign_first_row(
-            layouter.namespace(|| "first_row"),
-            self.a, self.b,
-            )?;
-        let _i: i32;
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FibonacciChip::construct(config);
 
-        let _ = chip.expose_public(layouter.namespace(|| "private a"), &_prev_a, 0);
-        let _ = chip.expose_public(layouter.namespace(|| "prvate b"), &prev_b, 1);
-        
-        for _i in 3..10 {
-            let c_cell = chip.assign_row(
-                 layouter.namespace(|| "next_row"),
-                &prev_b, 
-                &prev_c,
-            )?;
-            prev_b = prev_c;
-            prev_c = c_cell;
-        }
+        let (a_cell, b_cell, out_cell) =
+            chip.assign_with_seeds(layouter.namespace(|| "fibonacci"), self.a, self.b, 10)?;
 
-        let _ = chip.expose_public(layouter.namespace(|| "out"), &prev_c, 2);
+        chip.expose_public(layouter.namespace(|| "private a"), a_cell, 0)?;
+        chip.expose_public(layouter.namespace(|| "private b"), b_cell, 1)?;
+        chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)?;
 
         Ok(())
     }
-
-    
 }
 
-// fn main() {
-//     let k = 4;
-
-//     let a: Fp = Fp::from(1);
-//     let b: Fp = Fp::from(1);
-//     let out: Fp = Fp::from(55);
-
-//     let circuit = MyCircuit {
-//         a: Some(a),
-//         b: Some(b),
-//     };
-
-//     let mut  _public_input = vec![a, b, out];
-
-//     let prover = MockProver::run(k, &circuit, vec![_public_input.clone()]).unwrap();
-//     prover.assert_satisfied();
-    
-//     // _public_input[2] += Fp::one();
-//     // let prover = MockProver::run(k, &circuit, vec![_public_input.clone()]).unwrap();
-//     // prover.assert_satisfied();
-
-// }
-
-
 #[cfg(test)]
 mod tests {
-    use std::marker::PhantomData;
-
     use super::MyCircuit;
-    use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
 
     #[test]
     fn fibonacci_example1() {
@@ -226,10 +61,10 @@ mod tests {
         let b = Fp::from(1); // F[1]
         let out = Fp::from(55); // F[9]
 
-    let circuit = MyCircuit {
-        a: Some(a),
-        b: Some(b),
-    };
+        let circuit = MyCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
 
         let mut public_input = vec![a, b, out];
 
@@ -237,9 +72,8 @@ mod tests {
         prover.assert_satisfied();
 
         public_input[2] += Fp::one();
-        let _prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
-        // uncomment the following line and the assert will fail
-        // _prover.assert_satisfied();
+        let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err());
     }
 
     #[cfg(feature = "dev-graph")]
@@ -247,22 +81,16 @@ mod tests {
     fn plot_fibonacci1() {
         use plotters::prelude::*;
 
-        let a = Fp::from(1); // F[0]
-        let b = Fp::from(1); // F[1]
-        let out = Fp::from(55); // F[9]
-
-
         let root = BitMapBackend::new("fib-1-layout.png", (1024, 3096)).into_drawing_area();
         root.fill(&WHITE).unwrap();
         let root = root.titled("Fib 1 Layout", ("sans-serif", 60)).unwrap();
 
-  let circuit = MyCircuit {
-        a: Some(a),
-        b: Some(b),
-    };
-
+        let circuit = MyCircuit {
+            a: Value::known(Fp::from(1)),
+            b: Value::known(Fp::from(1)),
+        };
         halo2_proofs::dev::CircuitLayout::default()
             .render(4, &circuit, &root)
             .unwrap();
     }
-}
\ No newline at end of file
+}
@@ -1,124 +1,13 @@
-use halo2_proofs::{arithmetic::FieldExt, circuit::*, dev::MockProver, pasta::Fp, plonk::*, poly::Rotation};
-use std::marker::PhantomData;
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
 
-// #[derive(Debug, Clone)]
-// struct ACell<F: FieldExt>(AssignedCell<F, F>);
-
-#[derive(Debug, Clone)]
-struct FibonacciConfig {
-    advice: Column<Advice>,
-    selector: Selector,
-    instance: Column<Instance>,
-}
-
-#[derive(Debug, Clone)]
-struct FibonacciChip<F: FieldExt> {
-    config: FibonacciConfig,
-    _marker: PhantomData<F>,
-}
-
-impl<F: FieldExt> FibonacciChip<F> {
-    pub fn construct(config: FibonacciConfig) -> Self {
-        Self {
-            config,
-            _marker: PhantomData,
-        }
-    }
-
-    pub fn configure(
-        meta: &mut ConstraintSystem<F>,
-        advice: Column<Advice>,
-        instance: Column<Instance>,
-    ) -> FibonacciConfig {
-        let selector = meta.selector();
-
-        meta.enable_equality(advice);
-        meta.enable_equality(instance);
-
-        meta.create_gate("add", |meta| {
-            //
-            // advice | selector
-            //   a    |   s
-            //   b    |
-            //   c    |
-            //
-            let s = meta.query_selector(selector);
-            let a = meta.query_advice(advice, Rotation::cur());
-            let b = meta.query_advice(advice, Rotation::next());
-            let c = meta.query_advice(advice, Rotation(2));
-            vec![s * (a + b - c)]
-        });
-
-        FibonacciConfig {
-            advice,
-            selector,
-            instance,
-        }
-    }
-
-    pub fn assign(
-        &self,
-        mut layouter: impl Layouter<F>,
-        nrows: usize,
-    ) -> Result<AssignedCell<F, F>, Error> {
-        layouter.assign_region(
-            || "entire fibonacci table",
-            |mut region| {
-                self.config.selector.enable(&mut region, 0)?;
-                self.config.selector.enable(&mut region, 1)?;
-
-                let mut a_cell = region.assign_advice_from_instance(
-                    || "1",
-                    self.config.instance,
-                    0,
-                    self.config.advice,
-                    0,
-                )?;
-                let mut b_cell = region.assign_advice_from_instance(
-                    || "1",
-                    self.config.instance,
-                    1,
-                    self.config.advice,
-                    1,
-                )?;
-
-                for row in 2..nrows {
-                    if row < nrows - 2 {
-                        self.config.selector.enable(&mut region, row)?;
-                    }
-
-                    let c_val = a_cell.value().and_then(
-                        |a| {
-                            b_cell.value().map(|b| *a + *b)
-                        });
-                    let c_cell = region.assign_advice(
-                        || "advice", 
-                        self.config.advice,
-                        row, 
-                        || c_val.ok_or(Error::Synthesis),
-                        )?;
-    
-                    a_cell = b_cell;
-                    b_cell = c_cell;
-                }
-
-                Ok(b_cell)
-            },
-        )
-    }
-
-    pub fn expose_public(
-        &self,
-        mut layouter: impl Layouter<F>,
-        cell: AssignedCell<F, F>,
-        row: usize,
-    ) -> Result<(), Error> {
-        layouter.constrain_instance(cell.cell(), self.config.instance, row)
-    }
-}
+use super::{FibonacciChip, FibonacciConfig, FibonacciInstructions, FibonacciLayout};
 
+/// Single-column layout with private seeds, exposed as public inputs.
 #[derive(Default)]
-struct MyCircuit<F>(PhantomData<F>);
+pub struct MyCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+}
 
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     type Config = FibonacciConfig;
@@ -131,7 +20,8 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let advice = meta.advice_column();
         let instance = meta.instance_column();
-        FibonacciChip::configure(meta, advice, instance)
+
+        FibonacciChip::configure(meta, FibonacciLayout::SingleColumn(advice), instance)
     }
 
     fn synthesize(
@@ -141,8 +31,11 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     ) -> Result<(), Error> {
         let chip = FibonacciChip::construct(config);
 
-        let out_cell = chip.assign(layouter.namespace(|| "entire table"), 10)?;
+        let (a_cell, b_cell, out_cell) =
+            chip.assign_with_seeds(layouter.namespace(|| "entire table"), self.a, self.b, 10)?;
 
+        chip.expose_public(layouter.namespace(|| "private a"), a_cell, 0)?;
+        chip.expose_public(layouter.namespace(|| "private b"), b_cell, 1)?;
         chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)?;
 
         Ok(())
@@ -152,62 +45,28 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
 #[cfg(test)]
 mod tests {
     use super::MyCircuit;
-    use std::marker::PhantomData;
-    use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
 
     #[test]
-    fn fibonacci_example2() {
+    fn fibonacci_example3() {
         let k = 4;
 
         let a = Fp::from(1); // F[0]
         let b = Fp::from(1); // F[1]
         let out = Fp::from(55); // F[9]
 
-        let circuit = MyCircuit::<Fp>(PhantomData);
-        
+        let circuit = MyCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+
         let mut public_input = vec![a, b, out];
 
         let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
         prover.assert_satisfied();
 
         public_input[2] += Fp::one();
-        let _prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
-        // uncomment the following line and the assert will fail
-        // _prover.assert_satisfied();
-        println!("Fibonacci circuit verified successfully!");
-    }
-
-    #[cfg(feature = "dev-graph")]
-    #[test]
-    fn plot_fibo2() {
-        use plotters::prelude::*;
-        let root = BitMapBackend::new("fib-2-layout.png", (1024, 3096)).into_drawing_area();
-        root.fill(&WHITE).unwrap();
-        let root = root.titled("Fib 2 Layout", ("sans-serif", 60)).unwrap();
-
-        let circuit = MyCircuit::<Fp>(PhantomData);
-        halo2_proofs::dev::CircuitLayout::default()
-            .render(4, &circuit, &root)
-            .unwrap();
+        let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err());
     }
 }
-
-// fn main() {
-//     let k = 4;
-
-//     let a: Fp = Fp::from(1);
-//     let b: Fp = Fp::from(1);
-//     let out: Fp = Fp::from(55);
-
-//     let circuit = MyCircuit::<Fp>(PhantomData);
-        
-//     let mut  _public_input = vec![a, b, out];
-
-//     let prover = MockProver::run(k, &circuit, vec![_public_input.clone()]).unwrap();
-//     prover.assert_satisfied();
-    
-//     _public_input[2] += Fp::one();
-//     let prover = MockProver::run(k, &circuit, vec![_public_input.clone()]).unwrap();
-//     prover.assert_satisfied();
-
-// }
@@ -1,156 +1,12 @@
 use std::marker::PhantomData;
-use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
-
-#[derive(Debug, Clone)]
-struct FibonacciConfig {
-    pub col_a: Column<Advice>,
-    pub col_b: Column<Advice>,
-    pub col_c: Column<Advice>,
-    pub selector: Selector,
-    pub instance: Column<Instance>,
-}
-
-#[derive(Debug, Clone)]
-struct FibonacciChip<F: FieldExt> {
-    config: FibonacciConfig,
-    _marker: PhantomData<F>,
-}
-
-impl<F: FieldExt> FibonacciChip<F> {
-    pub fn construct(config: FibonacciConfig) -> Self {
-        Self {
-            config,
-            _marker: PhantomData,
-        }
-    }
-
-    pub fn configure(meta: &mut ConstraintSystem<F>) -> FibonacciConfig {
-        let col_a = meta.advice_column();
-        let col_b = meta.advice_column();
-        let col_c = meta.advice_column();
-        let selector = meta.selector();
-        let instance = meta.instance_column();
-
-        meta.enable_equality(col_a);
-        meta.enable_equality(col_b);
-        meta.enable_equality(col_c);
-        meta.enable_equality(instance);
-
-        meta.create_gate("add", |meta| {
-            //
-            // col_a | col_b | col_c | selector
-            //   a      b        c       s
-            //
-            let s = meta.query_selector(selector);
-            let a = meta.query_advice(col_a, Rotation::cur());
-            let b = meta.query_advice(col_b, Rotation::cur());
-            let c = meta.query_advice(col_c, Rotation::cur());
-            vec![s * (a + b - c)]
-        });
-
-        FibonacciConfig {
-            col_a,
-            col_b,
-            col_c,
-            selector,
-            instance,
-        }
-    }
-
-    #[allow(clippy::type_complexity)]
-    pub fn assign_first_row(
-        &self,
-        mut layouter: impl Layouter<F>,
-    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>), Error> {
-        layouter.assign_region(
-            || "first row",
-            |mut region| {
-                self.config.selector.enable(&mut region, 0)?;
-
-                let a_cell = region.assign_advice_from_instance(
-                    || "f(0)",
-                    self.config.instance,
-                    0,
-                    self.config.col_a,
-                    0)?;
-
-                let b_cell = region.assign_advice_from_instance(
-                    || "f(1)",
-                    self.config.instance,
-                    1,
-                    self.config.col_b,
-                    0)?;
-
-                    let c_val = a_cell.value().copied().and_then(|a| b_cell.value().copied().map(|b| a+b));
-
-                    let c_cell = region.assign_advice(
-                        || "c",
-                        self.config.col_c, 
-                        0,
-                        || c_val.ok_or(Error::Synthesis),
-                    )?;
-        
-                Ok((a_cell, b_cell, c_cell))
-            },
-        )
-    }
 
-    pub fn assign_row(
-        &self,
-        mut layouter: impl Layouter<F>,
-        prev_b: &AssignedCell<F, F>,
-        prev_c: &AssignedCell<F, F>,
-    ) -> Result<AssignedCell<F, F>, Error> {
-        layouter.assign_region(
-            || "next row",
-            |mut region| {
-                self.config.selector.enable(&mut region, 0)?;
-
-                // Copy the value from b & c in previous row to a & b in current row
-                prev_b.copy_advice(
-                    || "a",
-                    &mut region,
-                    self.config.col_a,
-                    0,
-                )?;
-                prev_c.copy_advice(
-                    || "b",
-                    &mut region,
-                    self.config.col_b,
-                    0,
-                )?;
-
-                let c_val = prev_b.value().and_then(
-                    |b|{
-                        prev_c.value().map(|c| *b + *c)
-                    }
-                );
-
-
-                let c_cell = region.assign_advice(
-                    || "c",
-                    self.config.col_c, 
-                    0,
-                    || c_val.ok_or(Error::Synthesis),
-                )?;
-    
-                Ok(c_cell)
-            },
-        )
-    }
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
 
-    pub fn expose_public(
-        &self,
-        mut layouter: impl Layouter<F>,
-        cell: &AssignedCell<F, F>,
-        row: usize,
-    ) -> Result<(), Error> {
-        layouter.constrain_instance(cell.cell(), self.config.instance, row)
-    }
-}
+use super::{FibonacciChip, FibonacciConfig, FibonacciInstructions, FibonacciLayout};
 
+/// Row-per-step layout, seeded from the instance column.
 #[derive(Default)]
-struct MyCircuit<F>(PhantomData<F>);
+pub struct MyCircuit<F>(pub PhantomData<F>);
 
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     type Config = FibonacciConfig;
@@ -161,7 +17,16 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        FibonacciChip::configure(meta)
+        let col_a = meta.advice_column();
+        let col_b = meta.advice_column();
+        let col_c = meta.advice_column();
+        let instance = meta.instance_column();
+
+        FibonacciChip::configure(
+            meta,
+            FibonacciLayout::RowPerStep([col_a, col_b, col_c]),
+            instance,
+        )
     }
 
     fn synthesize(
@@ -171,16 +36,9 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     ) -> Result<(), Error> {
         let chip = FibonacciChip::construct(config);
 
-        let (_, mut prev_b, mut prev_c) =
-            chip.assign_first_row(layouter.namespace(|| "first row"))?;
+        let out_cell = chip.assign(layouter.namespace(|| "fibonacci"), 10)?;
 
-        for _i in 3..10 {
-            let c_cell = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
-            prev_b = prev_c;
-            prev_c = c_cell;
-        }
-
-        chip.expose_public(layouter.namespace(|| "out"), &prev_c, 2)?;
+        chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)?;
 
         Ok(())
     }
@@ -209,9 +67,8 @@ mod tests {
         prover.assert_satisfied();
 
         public_input[2] += Fp::one();
-        let _prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
-        // uncomment the following line and the assert will fail
-        // _prover.assert_satisfied();
+        let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err());
     }
 
     #[cfg(feature = "dev-graph")]
@@ -228,4 +85,4 @@ mod tests {
             .render(4, &circuit, &root)
             .unwrap();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,348 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+/// Computes `(F(n), F(n + 1))` natively by fast doubling, with `F(0) = 0`
+/// and `F(1) = 1`.
+pub fn fast_fibonacci<F: FieldExt>(n: u64) -> (F, F) {
+    (0..u64::BITS - n.leading_zeros())
+        .rev()
+        .fold((F::zero(), F::one()), |(x, y), i| {
+            let (d0, d1) = double(x, y);
+            if (n >> i) & 1 == 1 {
+                (d1, d0 + d1)
+            } else {
+                (d0, d1)
+            }
+        })
+}
+
+/// `F(2k) = F(k)(2F(k+1) - F(k))` and `F(2k+1) = F(k)^2 + F(k+1)^2`.
+fn double<F: FieldExt>(x: F, y: F) -> (F, F) {
+    (x * (y.double() - x), x.square() + y.square())
+}
+
+/// Computes `F(n)` for any `n` in `O(log n)` rows. Each row consumes one bit of
+/// `n`, most significant first, doubling the index and adding the bit:
+///
+///   bit  |    n    |     x     |     y       | q_step | q_out
+///        |    0    |   F(0)    |   F(1)      |        |
+///   b_0  |   k_1   |  F(k_1)   |  F(k_1 + 1) |   1    |
+///   ...  |   ...   |   ...     |    ...      |  ...   |
+///   b_m  |    n    |   F(n)    |  F(n + 1)   |   1    |
+///        |   out   |     a     |     b       |        |   1
+///
+/// The last row maps the standard sequence onto the seeds `a`, `b` taken from
+/// rows 0 and 1 of the instance column: `out = a·F(n - 1) + b·F(n)`.
+#[derive(Debug, Clone)]
+pub struct FastDoublingConfig {
+    pub bit: Column<Advice>,
+    pub n: Column<Advice>,
+    pub x: Column<Advice>,
+    pub y: Column<Advice>,
+    pub instance: Column<Instance>,
+    pub q_step: Selector,
+    pub q_out: Selector,
+}
+
+#[derive(Debug, Clone)]
+pub struct FastDoublingChip<F: FieldExt> {
+    config: FastDoublingConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FastDoublingChip<F> {
+    pub fn construct(config: FastDoublingConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        constant: Column<Fixed>,
+        instance: Column<Instance>,
+    ) -> FastDoublingConfig {
+        let [bit, n, x, y] = advice;
+
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        let q_step = meta.selector();
+        let q_out = meta.selector();
+
+        meta.create_gate("double", |meta| {
+            let s = meta.query_selector(q_step);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let n_prev = meta.query_advice(n, Rotation::prev());
+            let n = meta.query_advice(n, Rotation::cur());
+            let x_prev = meta.query_advice(x, Rotation::prev());
+            let y_prev = meta.query_advice(y, Rotation::prev());
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+
+            let one = Expression::Constant(F::one());
+            let two = Expression::Constant(F::from(2));
+
+            // F(2k) and F(2k + 1)
+            let d0 = x_prev.clone() * (two.clone() * y_prev.clone() - x_prev.clone());
+            let d1 = x_prev.clone() * x_prev + y_prev.clone() * y_prev;
+
+            Constraints::with_selector(
+                s,
+                [
+                    ("bool", bit.clone() * (one - bit.clone())),
+                    ("decompose", n - (two * n_prev + bit.clone())),
+                    (
+                        "x",
+                        x - (d0.clone() + bit.clone() * (d1.clone() - d0.clone())),
+                    ),
+                    ("y", y - (d1 + bit * d0)),
+                ],
+            )
+        });
+
+        meta.create_gate("seeds", |meta| {
+            let s = meta.query_selector(q_out);
+            let out = meta.query_advice(n, Rotation::cur());
+            let a = meta.query_advice(x, Rotation::cur());
+            let b = meta.query_advice(y, Rotation::cur());
+            let x_prev = meta.query_advice(x, Rotation::prev());
+            let y_prev = meta.query_advice(y, Rotation::prev());
+
+            // F(n - 1) = F(n + 1) - F(n)
+            vec![s * (out - (a * (y_prev - x_prev.clone()) + b * x_prev))]
+        });
+
+        FastDoublingConfig {
+            bit,
+            n,
+            x,
+            y,
+            instance,
+            q_step,
+            q_out,
+        }
+    }
+
+    /// Decomposes `n` into `bits` bits and lays out `F(n)` for the seeds in rows
+    /// 0 and 1 of the instance column. Returns the recomposed `n` and the output.
+    #[allow(clippy::type_complexity)]
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: Value<u64>,
+        bits: usize,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        layouter.assign_region(
+            || "fast doubling",
+            |mut region| {
+                let config = &self.config;
+
+                let mut n_cell =
+                    region.assign_advice_from_constant(|| "0", config.n, 0, F::zero())?;
+                let mut x_cell =
+                    region.assign_advice_from_constant(|| "F(0)", config.x, 0, F::zero())?;
+                let mut y_cell =
+                    region.assign_advice_from_constant(|| "F(1)", config.y, 0, F::one())?;
+
+                for row in 1..=bits {
+                    config.q_step.enable(&mut region, row)?;
+
+                    let bit = n.map(|n| F::from((n >> (bits - row)) & 1));
+                    region.assign_advice(|| "bit", config.bit, row, || bit)?;
+
+                    let n_val = n_cell.value().map(|n| n.double()) + bit;
+                    let xy = x_cell
+                        .value()
+                        .zip(y_cell.value())
+                        .zip(bit)
+                        .map(|((x, y), bit)| {
+                            let (d0, d1) = double(*x, *y);
+                            if bit == F::one() {
+                                (d1, d0 + d1)
+                            } else {
+                                (d0, d1)
+                            }
+                        });
+                    let (x, y) = xy.unzip();
+
+                    n_cell = region.assign_advice(|| "n", config.n, row, || n_val)?;
+                    x_cell = region.assign_advice(|| "x", config.x, row, || x)?;
+                    y_cell = region.assign_advice(|| "y", config.y, row, || y)?;
+                }
+
+                let row = bits + 1;
+                config.q_out.enable(&mut region, row)?;
+                let a = region.assign_advice_from_instance(
+                    || "a",
+                    config.instance,
+                    0,
+                    config.x,
+                    row,
+                )?;
+                let b = region.assign_advice_from_instance(
+                    || "b",
+                    config.instance,
+                    1,
+                    config.y,
+                    row,
+                )?;
+
+                let out = a.value().copied() * (y_cell.value().copied() - x_cell.value())
+                    + b.value().copied() * x_cell.value();
+                let out_cell = region.assign_advice(|| "out", config.n, row, || out)?;
+
+                Ok((n_cell, out_cell))
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+/// Same public inputs as `FibonacciCircuit`, `[F(0), F(1), F(n)]`, with
+/// `O(log n)` rows.
+#[derive(Clone, Debug)]
+pub struct FastFibonacciCircuit<F> {
+    pub n: u64,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FastFibonacciCircuit<F> {
+    pub fn new(n: u64) -> Self {
+        Self {
+            n,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Number of bits of `n`, at least one.
+    pub fn bits(&self) -> usize {
+        std::cmp::max(u64::BITS - self.n.leading_zeros(), 1) as usize
+    }
+
+    /// Smallest `k` whose usable rows fit the table, after the blinding rows.
+    pub fn k(&self) -> u32 {
+        let mut meta = ConstraintSystem::<F>::default();
+        Self::configure(&mut meta);
+
+        let rows = std::cmp::max(
+            self.bits() + 2 + meta.blinding_factors() + 1,
+            meta.minimum_rows(),
+        );
+        rows.next_power_of_two().trailing_zeros()
+    }
+
+    /// The expected public inputs `[F(0), F(1), F(n)]`.
+    pub fn public_inputs(&self, a: F, b: F) -> Vec<F> {
+        let (x, y) = fast_fibonacci::<F>(self.n);
+        vec![a, b, a * (y - x) + b * x]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FastFibonacciCircuit<F> {
+    type Config = FastDoublingConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // n is part of the circuit shape, the seeds live in the instance column
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+
+        FastDoublingChip::configure(meta, advice, constant, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FastDoublingChip::construct(config.clone());
+
+        let (n_cell, out_cell) = chip.assign(
+            layouter.namespace(|| "fast doubling"),
+            Value::known(self.n),
+            self.bits(),
+        )?;
+
+        // the index is part of the statement, not a free witness
+        layouter.assign_region(
+            || "n",
+            |mut region| {
+                let n = region.assign_advice_from_constant(|| "n", config.n, 0, F::from(self.n))?;
+                region.constrain_equal(n_cell.cell(), n.cell())
+            },
+        )?;
+
+        chip.expose_public(layouter.namespace(|| "out"), &out_cell, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fast_fibonacci, FastFibonacciCircuit};
+    use crate::fibonacci::fibonacci;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    #[test]
+    fn fast_fibonacci_native() {
+        for n in 0..50 {
+            let (x, y) = fast_fibonacci::<Fp>(n);
+            assert_eq!(x, fibonacci(Fp::zero(), Fp::one(), n as usize));
+            assert_eq!(y, fibonacci(Fp::zero(), Fp::one(), n as usize + 1));
+        }
+    }
+
+    #[test]
+    fn fast_fibonacci_small() {
+        let (a, b) = (Fp::from(1), Fp::from(1));
+
+        for n in [0, 1, 2, 9, 10, 33] {
+            let circuit = FastFibonacciCircuit::<Fp>::new(n);
+            let public_input = circuit.public_inputs(a, b);
+            assert_eq!(public_input[2], fibonacci(a, b, n as usize));
+
+            let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+            prover.assert_satisfied();
+        }
+
+        let circuit = FastFibonacciCircuit::<Fp>::new(9);
+        assert_eq!(circuit.public_inputs(a, b)[2], Fp::from(55));
+        let prover =
+            MockProver::run(circuit.k(), &circuit, vec![vec![a, b, Fp::from(56)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn fast_fibonacci_2_pow_20() {
+        let circuit = FastFibonacciCircuit::<Fp>::new(1 << 20);
+        assert_eq!(circuit.k(), 5);
+
+        let mut public_input = circuit.public_inputs(Fp::zero(), Fp::one());
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        public_input[2] += Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,341 @@
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+use super::{fibonacci, FibonacciChip, FibonacciConfig, FibonacciInstructions, FibonacciLayout};
+use crate::range_check::{RangeCheckChip, RangeCheckConfig};
+
+/// Bits per limb of the decomposition, each limb is checked against
+/// `LIMB_RANGE` by a `RangeCheckChip`, which settles on a lookup.
+pub const LIMB_BITS: usize = 4;
+pub const LIMB_RANGE: usize = 1 << LIMB_BITS;
+
+/// Fibonacci over the integers: every term is decomposed into `BITS / LIMB_BITS`
+/// limbs with a running sum, so a term of `BITS` bits or more cannot be laid out.
+///
+///    z    | limb | q_decompose | range check
+///  v = z_0 | l_0  |      1      |       1
+///    z_1  | l_1  |      1      |       1
+///    ...  | ...  |     ...     |      ...
+///    z_L  |      |             |
+///
+/// with `z_i = 16·z_{i+1} + l_i`, every `l_i < 16` and `z_L = 0`.
+#[derive(Debug, Clone)]
+pub struct CheckedFibonacciConfig<F: FieldExt> {
+    pub fibonacci: FibonacciConfig,
+    pub z: Column<Advice>,
+    pub limb: RangeCheckConfig<F>,
+    pub q_decompose: Selector,
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckedFibonacciChip<F: FieldExt, const BITS: usize> {
+    config: CheckedFibonacciConfig<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> CheckedFibonacciChip<F, BITS> {
+    pub fn construct(config: CheckedFibonacciConfig<F>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        z: Column<Advice>,
+        limb: Column<Advice>,
+        constant: Column<Fixed>,
+        instance: Column<Instance>,
+    ) -> CheckedFibonacciConfig<F> {
+        assert_eq!(
+            BITS % LIMB_BITS,
+            0,
+            "the bit width must be a multiple of LIMB_BITS"
+        );
+        // otherwise the running sum could wrap around the modulus
+        assert!(
+            BITS + LIMB_BITS < F::NUM_BITS as usize,
+            "the bit width does not fit in the field"
+        );
+
+        let fibonacci =
+            FibonacciChip::configure(meta, FibonacciLayout::RowPerStep(advice), instance);
+        let limb = RangeCheckChip::configure(meta, limb, F::from(LIMB_RANGE as u64));
+        let q_decompose = meta.selector();
+
+        meta.enable_equality(z);
+        meta.enable_constant(constant);
+
+        meta.create_gate("decompose", |meta| {
+            let s = meta.query_selector(q_decompose);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+            let l = meta.query_advice(limb.value, Rotation::cur());
+
+            let radix = Expression::Constant(F::from(LIMB_RANGE as u64));
+            vec![s * (z_cur - (radix * z_next + l))]
+        });
+
+        CheckedFibonacciConfig {
+            fibonacci,
+            z,
+            limb,
+            q_decompose,
+        }
+    }
+
+    /// Loads the table of the limb range check, once per circuit.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        RangeCheckChip::construct(self.config.limb.clone()).load(layouter)
+    }
+
+    /// Lays out `F(0)..=F(n)` from two private seeds, checking every term is
+    /// below `2^BITS`. Returns the cells holding both seeds and `F(n)`.
+    #[allow(clippy::type_complexity)]
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        n: usize,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let chip = FibonacciChip::construct(self.config.fibonacci.clone());
+
+        let (mut a, mut b) = chip.load_seeds(layouter.namespace(|| "seeds"), a, b)?;
+        self.range_check(layouter.namespace(|| "range check F(0)"), &a)?;
+        self.range_check(layouter.namespace(|| "range check F(1)"), &b)?;
+        let seeds = (a.clone(), b.clone());
+
+        for i in 2..=n {
+            let c = chip.step(layouter.namespace(|| format!("F({})", i)), a, b.clone())?;
+            self.range_check(layouter.namespace(|| format!("range check F({})", i)), &c)?;
+            a = b;
+            b = c;
+        }
+
+        let out = if n == 0 { seeds.0.clone() } else { b };
+        Ok((seeds.0, seeds.1, out))
+    }
+
+    /// Constrains `cell` to be below `2^BITS`.
+    pub fn range_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        let limb_chip = RangeCheckChip::construct(config.limb.clone());
+        let radix_inv = F::from(LIMB_RANGE as u64).invert().unwrap();
+
+        layouter.assign_region(
+            || "decompose",
+            |mut region| {
+                let mut z = cell.copy_advice(|| "z_0", &mut region, config.z, 0)?;
+
+                for row in 0..BITS / LIMB_BITS {
+                    config.q_decompose.enable(&mut region, row)?;
+
+                    let limb = z
+                        .value()
+                        .map(|z| F::from_u128(z.get_lower_128() & (LIMB_RANGE as u128 - 1)));
+                    limb_chip.assign_in_region(&mut region, row, limb)?;
+
+                    let next = (z.value().copied() - limb) * Value::known(radix_inv);
+                    z = region.assign_advice(|| "z", config.z, row + 1, || next)?;
+                }
+
+                // whatever is left over did not fit in BITS bits
+                z.value().error_if_known_and(|z| **z != F::zero())?;
+                region.constrain_constant(z.cell(), F::zero())
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        FibonacciChip::construct(self.config.fibonacci.clone()).expose_public(layouter, cell, row)
+    }
+}
+
+/// Proves `F(n)` over the integers for terms of at most `BITS` bits, with the
+/// public inputs `[F(0), F(1), F(n)]`.
+#[derive(Clone, Debug, Default)]
+pub struct CheckedFibonacciCircuit<F, const BITS: usize> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+}
+
+impl<F: FieldExt, const BITS: usize> CheckedFibonacciCircuit<F, BITS> {
+    pub fn new(a: F, b: F, n: usize) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            n,
+        }
+    }
+
+    /// Smallest `k` whose usable rows fit the decomposition of every term and
+    /// the limb table.
+    pub fn k(&self) -> u32 {
+        let mut meta = ConstraintSystem::<F>::default();
+        Self::configure(&mut meta);
+
+        let rows = std::cmp::max((self.n + 1) * (BITS / LIMB_BITS + 1), LIMB_RANGE);
+        let rows = std::cmp::max(rows + meta.blinding_factors() + 1, meta.minimum_rows());
+        rows.next_power_of_two().trailing_zeros()
+    }
+
+    /// The expected public inputs `[F(0), F(1), F(n)]`.
+    pub fn public_inputs(a: F, b: F, n: usize) -> Vec<F> {
+        vec![a, b, fibonacci(a, b, n)]
+    }
+}
+
+impl<F: FieldExt, const BITS: usize> Circuit<F> for CheckedFibonacciCircuit<F, BITS> {
+    type Config = CheckedFibonacciConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            ..Self::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let z = meta.advice_column();
+        let limb = meta.advice_column();
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+
+        CheckedFibonacciChip::<F, BITS>::configure(meta, advice, z, limb, constant, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = CheckedFibonacciChip::<F, BITS>::construct(config);
+        chip.load(&mut layouter)?;
+
+        let (a, b, out) =
+            chip.assign(layouter.namespace(|| "fibonacci"), self.a, self.b, self.n)?;
+
+        chip.expose_public(layouter.namespace(|| "a"), a, 0)?;
+        chip.expose_public(layouter.namespace(|| "b"), b, 1)?;
+        chip.expose_public(layouter.namespace(|| "out"), out, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range_check::RangeCheckStrategy;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    #[test]
+    fn fibonacci_u64() {
+        // the limbs are looked up, a degree 18 gate would dwarf the rest
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let config = CheckedFibonacciCircuit::<Fp, 64>::configure(&mut meta);
+        assert!(matches!(
+            config.limb.strategy,
+            RangeCheckStrategy::Lookup { .. }
+        ));
+
+        let (a, b) = (Fp::zero(), Fp::one());
+
+        // F(93) is the largest Fibonacci number below 2^64
+        let circuit = CheckedFibonacciCircuit::<Fp, 64>::new(a, b, 93);
+        let public_input = CheckedFibonacciCircuit::<Fp, 64>::public_inputs(a, b, 93);
+        assert_eq!(public_input[2], Fp::from(12200160415121876738));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+
+        // F(94) wraps around u64
+        let circuit = CheckedFibonacciCircuit::<Fp, 64>::new(a, b, 94);
+        let public_input = CheckedFibonacciCircuit::<Fp, 64>::public_inputs(a, b, 94);
+        assert!(matches!(
+            MockProver::run(circuit.k(), &circuit, vec![public_input]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    /// A dishonest prover skipping the synthesis check still fails verification.
+    #[derive(Default)]
+    struct OverflowCircuit<F, const BITS: usize> {
+        value: Value<F>,
+    }
+
+    impl<F: FieldExt, const BITS: usize> Circuit<F> for OverflowCircuit<F, BITS> {
+        type Config = CheckedFibonacciConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            CheckedFibonacciCircuit::<F, BITS>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = CheckedFibonacciChip::<F, BITS>::construct(config.clone());
+            chip.load(&mut layouter)?;
+            let limb_chip = RangeCheckChip::construct(config.limb.clone());
+
+            // claim the value fits in BITS bits by dropping its top limb
+            let radix = F::from(LIMB_RANGE as u64);
+            let limbs = BITS / LIMB_BITS;
+            layouter.assign_region(
+                || "decompose",
+                |mut region| {
+                    let mut z = self.value;
+                    for row in 0..limbs {
+                        config.q_decompose.enable(&mut region, row)?;
+                        region.assign_advice(|| "z", config.z, row, || z)?;
+
+                        let limb = z.map(|z| F::from_u128(z.get_lower_128() % LIMB_RANGE as u128));
+                        limb_chip.assign_in_region(&mut region, row, limb)?;
+                        z = (z - limb) * Value::known(radix.invert().unwrap());
+                    }
+                    let last = region.assign_advice(
+                        || "z",
+                        config.z,
+                        limbs,
+                        || Value::known(F::zero()),
+                    )?;
+                    region.constrain_constant(last.cell(), F::zero())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn overflow_fails_verification() {
+        let circuit = OverflowCircuit::<Fp, 8> {
+            value: Value::known(Fp::from(256 + 233)),
+        };
+        let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        let circuit = OverflowCircuit::<Fp, 8> {
+            value: Value::known(Fp::from(233)),
+        };
+        let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
+        prover.assert_satisfied();
+    }
+}
@@ -0,0 +1,296 @@
+use std::marker::PhantomData;
+
+use gadget::is_zero::{IsZeroChip, IsZeroConfig};
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+use super::fibonacci;
+
+/// Proves that a public `y` is `F(n)` for some private `n < N_MAX`, with
+/// `F(0) = 0` and `F(1) = 1`. Every row compares its index against `n`, and the
+/// matching term is summed into `acc`:
+///
+///  value | index |  n  |    n_inv    |  acc  | hit | q_fib
+///  F(0)  |   0   |  n  | 1/(n - 0)   |  y_0  | h_0 |   1
+///  F(1)  |   1   |  n  | 1/(n - 1)   |  y_1  | h_1 |   1
+///  ...   |  ...  | ... |    ...      |  ...  | ... |
+///
+/// `hit` counts the matching rows and must end at exactly one, so `n` cannot
+/// run past the table.
+#[derive(Debug, Clone)]
+pub struct PrivateIndexConfig<F: FieldExt> {
+    pub value: Column<Advice>,
+    pub index: Column<Fixed>,
+    pub n: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub hit: Column<Advice>,
+    pub constant: Column<Fixed>,
+    pub instance: Column<Instance>,
+    pub q_fib: Selector,
+    pub q_row: Selector,
+    pub q_first: Selector,
+    pub q_step: Selector,
+    pub index_equals_n: IsZeroConfig<F>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrivateIndexChip<F: FieldExt, const N_MAX: usize> {
+    config: PrivateIndexConfig<F>,
+}
+
+impl<F: FieldExt, const N_MAX: usize> PrivateIndexChip<F, N_MAX> {
+    pub fn construct(config: PrivateIndexConfig<F>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        constant: Column<Fixed>,
+        instance: Column<Instance>,
+    ) -> PrivateIndexConfig<F> {
+        let [value, n, n_inv, acc, hit] = advice;
+        let index = meta.fixed_column();
+
+        meta.enable_equality(value);
+        meta.enable_equality(acc);
+        meta.enable_equality(hit);
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        let q_fib = meta.selector();
+        let q_row = meta.selector();
+        let q_first = meta.selector();
+        let q_step = meta.selector();
+
+        let index_equals_n = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_row),
+            |meta| meta.query_advice(n, Rotation::cur()) - meta.query_fixed(index, Rotation::cur()),
+            n_inv,
+        );
+
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(q_fib);
+            let a = meta.query_advice(value, Rotation::cur());
+            let b = meta.query_advice(value, Rotation::next());
+            let c = meta.query_advice(value, Rotation(2));
+            vec![s * (a + b - c)]
+        });
+
+        meta.create_gate("select first", |meta| {
+            let s = meta.query_selector(q_first);
+            let value = meta.query_advice(value, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let hit = meta.query_advice(hit, Rotation::cur());
+            let eq = index_equals_n.expr();
+
+            vec![s.clone() * (acc - eq.clone() * value), s * (hit - eq)]
+        });
+
+        meta.create_gate("select", |meta| {
+            let s = meta.query_selector(q_step);
+            let value = meta.query_advice(value, Rotation::cur());
+            let n_prev = meta.query_advice(n, Rotation::prev());
+            let n = meta.query_advice(n, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let hit_prev = meta.query_advice(hit, Rotation::prev());
+            let hit = meta.query_advice(hit, Rotation::cur());
+            let eq = index_equals_n.expr();
+
+            vec![
+                // the private index is the same on every row
+                s.clone() * (n - n_prev),
+                s.clone() * (acc - acc_prev - eq.clone() * value),
+                s * (hit - hit_prev - eq),
+            ]
+        });
+
+        PrivateIndexConfig {
+            value,
+            index,
+            n,
+            acc,
+            hit,
+            constant,
+            instance,
+            q_fib,
+            q_row,
+            q_first,
+            q_step,
+            index_equals_n,
+        }
+    }
+
+    /// Lays out `F(0)..F(N_MAX - 1)` and returns the cell holding `F(n)`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let is_zero_chip = IsZeroChip::construct(self.config.index_equals_n.clone());
+
+        layouter.assign_region(
+            || "private index table",
+            |mut region| {
+                let mut values = vec![
+                    region.assign_advice_from_constant(
+                        || "F(0)",
+                        self.config.value,
+                        0,
+                        F::zero(),
+                    )?,
+                    region.assign_advice_from_constant(
+                        || "F(1)",
+                        self.config.value,
+                        1,
+                        F::one(),
+                    )?,
+                ];
+                for row in 2..N_MAX {
+                    self.config.q_fib.enable(&mut region, row - 2)?;
+                    let value = values[row - 2].value().copied() + values[row - 1].value();
+                    values.push(region.assign_advice(
+                        || "F(i)",
+                        self.config.value,
+                        row,
+                        || value,
+                    )?);
+                }
+
+                let mut acc = Value::known(F::zero());
+                let mut hit = Value::known(F::zero());
+                let mut last = None;
+                for (row, value) in values.iter().enumerate() {
+                    let index = F::from(row as u64);
+                    if row == 0 {
+                        self.config.q_first.enable(&mut region, row)?;
+                    } else {
+                        self.config.q_step.enable(&mut region, row)?;
+                    }
+                    self.config.q_row.enable(&mut region, row)?;
+
+                    region.assign_fixed(
+                        || "index",
+                        self.config.index,
+                        row,
+                        || Value::known(index),
+                    )?;
+                    region.assign_advice(|| "n", self.config.n, row, || n)?;
+                    is_zero_chip.assign(&mut region, row, n.map(|n| n - index))?;
+
+                    let eq = n.map(|n| if n == index { F::one() } else { F::zero() });
+                    acc = acc + eq * value.value();
+                    hit = hit + eq;
+
+                    let acc_cell = region.assign_advice(|| "acc", self.config.acc, row, || acc)?;
+                    let hit_cell = region.assign_advice(|| "hit", self.config.hit, row, || hit)?;
+                    last = Some((acc_cell, hit_cell));
+                }
+
+                // exactly one row matched, i.e. n < N_MAX
+                let (acc_cell, hit_cell) = last.ok_or(Error::Synthesis)?;
+                region.constrain_constant(hit_cell.cell(), F::one())?;
+
+                Ok(acc_cell)
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+/// The only public input is `y = F(n)`; `n` stays private.
+#[derive(Default)]
+pub struct PrivateIndexCircuit<F, const N_MAX: usize> {
+    pub n: Value<usize>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const N_MAX: usize> PrivateIndexCircuit<F, N_MAX> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n: Value::known(n),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The expected public input `F(n)`.
+    pub fn output(n: usize) -> F {
+        fibonacci(F::zero(), F::one(), n)
+    }
+}
+
+impl<F: FieldExt, const N_MAX: usize> Circuit<F> for PrivateIndexCircuit<F, N_MAX> {
+    type Config = PrivateIndexConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+
+        PrivateIndexChip::<F, N_MAX>::configure(meta, advice, constant, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = PrivateIndexChip::<F, N_MAX>::construct(config);
+
+        let n = self.n.map(|n| F::from(n as u64));
+        let y = chip.assign(layouter.namespace(|| "select F(n)"), n)?;
+
+        chip.expose_public(layouter.namespace(|| "y"), &y, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PrivateIndexCircuit;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    const N_MAX: usize = 20;
+
+    #[test]
+    fn private_index() {
+        let k = 5;
+
+        for n in [0, 1, 2, 10, N_MAX - 1] {
+            let circuit = PrivateIndexCircuit::<Fp, N_MAX>::new(n);
+            let y = PrivateIndexCircuit::<Fp, N_MAX>::output(n);
+
+            let prover = MockProver::run(k, &circuit, vec![vec![y]]).unwrap();
+            prover.assert_satisfied();
+        }
+
+        // F(10) = 55 is in the sequence, 56 is not at position 10
+        let circuit = PrivateIndexCircuit::<Fp, N_MAX>::new(10);
+        assert_eq!(PrivateIndexCircuit::<Fp, N_MAX>::output(10), Fp::from(55));
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(56)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn private_index_out_of_range() {
+        let k = 5;
+
+        // no row matches, so the prover can only claim y = 0
+        let circuit = PrivateIndexCircuit::<Fp, N_MAX>::new(N_MAX);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
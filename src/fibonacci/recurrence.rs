@@ -0,0 +1,243 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+/// Computes `x_n` natively for `x_n = c_1·x_{n-1} + ... + c_W·x_{n-W}`.
+pub fn linear_recurrence<F: FieldExt, const W: usize>(
+    seeds: [F; W],
+    coeffs: [F; W],
+    n: usize,
+) -> F {
+    let mut terms = seeds.to_vec();
+    while terms.len() <= n {
+        let len = terms.len();
+        let next = coeffs
+            .iter()
+            .enumerate()
+            .fold(F::zero(), |acc, (i, c)| acc + *c * terms[len - 1 - i]);
+        terms.push(next);
+    }
+    terms[n]
+}
+
+/// Order-`W` linear recurrence over a single advice column, with one fixed
+/// column per coefficient:
+///
+///  advice  | c_1 | ... | c_W | selector
+///  x_0     | c_1 | ... | c_W |    s
+///  ...     |     |     |     |
+///  x_W     |     |     |     |
+///
+/// Lucas uses `[1, 1]`, Pell `[2, 1]` and Tribonacci `[1, 1, 1]`.
+#[derive(Debug, Clone)]
+pub struct RecurrenceConfig<const W: usize> {
+    pub advice: Column<Advice>,
+    pub coeffs: [Column<Fixed>; W],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecurrenceChip<F: FieldExt, const W: usize> {
+    config: RecurrenceConfig<W>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const W: usize> RecurrenceChip<F, W> {
+    pub fn construct(config: RecurrenceConfig<W>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: Column<Advice>,
+        coeffs: [Column<Fixed>; W],
+        instance: Column<Instance>,
+    ) -> RecurrenceConfig<W> {
+        let selector = meta.selector();
+
+        meta.enable_equality(advice);
+        meta.enable_equality(instance);
+
+        meta.create_gate("linear recurrence", |meta| {
+            let s = meta.query_selector(selector);
+            let next = meta.query_advice(advice, Rotation(W as i32));
+
+            // c_i multiplies x_{n-i}, which sits i rows above x_n
+            let sum =
+                coeffs
+                    .iter()
+                    .enumerate()
+                    .fold(Expression::Constant(F::zero()), |acc, (i, c)| {
+                        let c = meta.query_fixed(*c, Rotation::cur());
+                        let x = meta.query_advice(advice, Rotation((W - 1 - i) as i32));
+                        acc + c * x
+                    });
+
+            vec![s * (sum - next)]
+        });
+
+        RecurrenceConfig {
+            advice,
+            coeffs,
+            selector,
+            instance,
+        }
+    }
+
+    /// Lays out `x_0..x_{nrows-1}` seeded from rows `0..W` of the instance
+    /// column, and returns the cell holding the last term. The seeds are always
+    /// loaded, so for `nrows <= W` the last term is one of them.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        coeffs: [F; W],
+        nrows: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        if nrows == 0 {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "recurrence table",
+            |mut region| {
+                let mut terms = Vec::with_capacity(nrows);
+                for row in 0..W {
+                    terms.push(region.assign_advice_from_instance(
+                        || format!("x_{}", row),
+                        self.config.instance,
+                        row,
+                        self.config.advice,
+                        row,
+                    )?);
+                }
+
+                for row in W..nrows {
+                    let offset = row - W;
+                    self.config.selector.enable(&mut region, offset)?;
+                    for (column, c) in self.config.coeffs.iter().zip(coeffs.iter()) {
+                        region.assign_fixed(
+                            || "coefficient",
+                            *column,
+                            offset,
+                            || Value::known(*c),
+                        )?;
+                    }
+
+                    let value = coeffs
+                        .iter()
+                        .enumerate()
+                        .fold(Value::known(F::zero()), |acc, (i, c)| {
+                            acc + terms[row - 1 - i].value().map(|x| *c * x)
+                        });
+                    terms.push(region.assign_advice(
+                        || format!("x_{}", row),
+                        self.config.advice,
+                        row,
+                        || value,
+                    )?);
+                }
+
+                Ok(terms[nrows - 1].clone())
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// Public inputs are the `W` seeds followed by `x_n`.
+    #[derive(Clone)]
+    struct MyCircuit<F, const W: usize> {
+        coeffs: [F; W],
+        n: usize,
+    }
+
+    impl<F: FieldExt, const W: usize> Circuit<F> for MyCircuit<F, W> {
+        type Config = RecurrenceConfig<W>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = meta.advice_column();
+            let coeffs = [(); W].map(|_| meta.fixed_column());
+            let instance = meta.instance_column();
+
+            RecurrenceChip::configure(meta, advice, coeffs, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = RecurrenceChip::construct(config);
+
+            let out = chip.assign(layouter.namespace(|| "recurrence"), self.coeffs, self.n + 1)?;
+            chip.expose_public(layouter.namespace(|| "out"), &out, W)
+        }
+    }
+
+    fn run<const W: usize>(seeds: [u64; W], coeffs: [u64; W], n: usize, out: u64) {
+        let seeds = seeds.map(Fp::from);
+        let coeffs = coeffs.map(Fp::from);
+        assert_eq!(linear_recurrence(seeds, coeffs, n), Fp::from(out));
+
+        let circuit = MyCircuit { coeffs, n };
+        let mut public_input = seeds.to_vec();
+        public_input.push(Fp::from(out));
+
+        let prover = MockProver::run(5, &circuit, vec![public_input.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        public_input[W] += Fp::one();
+        let prover = MockProver::run(5, &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn fibonacci() {
+        run([1, 1], [1, 1], 9, 55);
+    }
+
+    #[test]
+    fn lucas() {
+        run([2, 1], [1, 1], 9, 76);
+    }
+
+    #[test]
+    fn pell() {
+        run([0, 1], [2, 1], 9, 985);
+    }
+
+    #[test]
+    fn tribonacci() {
+        run([0, 0, 1], [1, 1, 1], 9, 44);
+    }
+
+    #[test]
+    fn seeds_only() {
+        for (n, out) in [4, 6, 9].into_iter().enumerate() {
+            run([4, 6, 9], [1, 1, 1], n, out);
+        }
+        run([4, 6, 9], [1, 1, 1], 3, 19);
+    }
+}
@@ -0,0 +1,392 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+/// Computes `x_n` natively for `x_{i+W} = step([x_i, ..., x_{i+W-1}])`.
+pub fn iterate<F: FieldExt, const W: usize>(
+    seeds: [F; W],
+    step: impl Fn([F; W]) -> F,
+    n: usize,
+) -> F {
+    let mut terms = seeds.to_vec();
+    while terms.len() <= n {
+        let window = std::array::from_fn(|i| terms[terms.len() - W + i]);
+        terms.push(step(window));
+    }
+    terms[n]
+}
+
+/// How the execution trace of a `TraceChip` is laid out in the main table.
+#[derive(Debug, Clone, Copy)]
+pub enum TraceLayout<const W: usize> {
+    /// A single advice column, the gate looks `W` rows ahead:
+    ///
+    ///  advice | selector
+    ///   x_0   |    s
+    ///   ...   |
+    ///   x_W   |
+    SingleColumn(Column<Advice>),
+    /// One advice column per term of the window, the gate looks one row ahead
+    /// and checks the window shifted by one term:
+    ///
+    ///  col_0 | ... | col_{W-1} | selector
+    ///   x_0  | ... |  x_{W-1}  |    s
+    ///   x_1  | ... |   x_W     |
+    MultiColumn([Column<Advice>; W]),
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceConfig<const W: usize> {
+    pub layout: TraceLayout<W>,
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// Proves iterated maps `x_{i+W} = step(x_i, ..., x_{i+W-1})` for any step
+/// that is a polynomial in the previous `W` terms. Fibonacci is
+/// `|[a, b]| a + b` in either layout.
+#[derive(Debug, Clone)]
+pub struct TraceChip<F: FieldExt, const W: usize> {
+    config: TraceConfig<W>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const W: usize> TraceChip<F, W> {
+    pub fn construct(config: TraceConfig<W>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// `step` builds the next term from the previous `W` terms, oldest first.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        layout: TraceLayout<W>,
+        instance: Column<Instance>,
+        step: impl Fn([Expression<F>; W]) -> Expression<F>,
+    ) -> TraceConfig<W> {
+        assert!(W > 0, "the step needs at least one previous term");
+
+        let selector = meta.selector();
+
+        meta.enable_equality(instance);
+
+        match layout {
+            TraceLayout::SingleColumn(advice) => {
+                meta.enable_equality(advice);
+
+                meta.create_gate("trace step", |meta| {
+                    let s = meta.query_selector(selector);
+                    let window =
+                        std::array::from_fn(|i| meta.query_advice(advice, Rotation(i as i32)));
+                    let next = meta.query_advice(advice, Rotation(W as i32));
+
+                    vec![s * (step(window) - next)]
+                });
+            }
+            TraceLayout::MultiColumn(advice) => {
+                for column in advice {
+                    meta.enable_equality(column);
+                }
+
+                meta.create_gate("trace step", |meta| {
+                    let s = meta.query_selector(selector);
+                    let cur = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+                    let next = advice.map(|column| meta.query_advice(column, Rotation::next()));
+
+                    // every term but the oldest moves one column to the left
+                    let mut constraints: Vec<_> = (1..W)
+                        .map(|i| cur[i].clone() - next[i - 1].clone())
+                        .collect();
+                    constraints.push(step(cur) - next[W - 1].clone());
+
+                    Constraints::with_selector(s, constraints)
+                });
+            }
+        }
+
+        TraceConfig {
+            layout,
+            selector,
+            instance,
+        }
+    }
+
+    /// Lays out `x_0..x_{nrows-1}` from the `W` private seeds, computing every
+    /// term with `step`. The selector is enabled on every row whose window and
+    /// next term are in the trace. Returns the cells holding every term.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        seeds: [Value<F>; W],
+        step: impl Fn([F; W]) -> F,
+        nrows: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        // the seeds and at least one constrained term
+        if nrows <= W {
+            return Err(Error::Synthesis);
+        }
+
+        let next = |terms: &[AssignedCell<F, F>]| {
+            let window = terms[terms.len() - W..]
+                .iter()
+                .fold(Value::known(vec![]), |acc, cell| {
+                    acc.zip(cell.value()).map(|(mut acc, x)| {
+                        acc.push(*x);
+                        acc
+                    })
+                });
+            window.map(|window| step(window.try_into().unwrap()))
+        };
+
+        match self.config.layout {
+            TraceLayout::SingleColumn(advice) => layouter.assign_region(
+                || "trace",
+                |mut region| {
+                    let mut terms = Vec::with_capacity(nrows);
+                    for (row, seed) in seeds.iter().enumerate() {
+                        terms.push(region.assign_advice(
+                            || format!("x_{}", row),
+                            advice,
+                            row,
+                            || *seed,
+                        )?);
+                    }
+
+                    for row in W..nrows {
+                        self.config.selector.enable(&mut region, row - W)?;
+
+                        let value = next(&terms);
+                        terms.push(region.assign_advice(
+                            || format!("x_{}", row),
+                            advice,
+                            row,
+                            || value,
+                        )?);
+                    }
+
+                    Ok(terms)
+                },
+            ),
+            TraceLayout::MultiColumn(advice) => layouter.assign_region(
+                || "trace",
+                |mut region| {
+                    let mut terms = Vec::with_capacity(nrows);
+                    for (i, seed) in seeds.iter().enumerate() {
+                        terms.push(region.assign_advice(
+                            || format!("x_{}", i),
+                            advice[i],
+                            0,
+                            || *seed,
+                        )?);
+                    }
+
+                    // row `r` holds the window `x_r..x_{r+W-1}`
+                    for row in 1..=nrows - W {
+                        self.config.selector.enable(&mut region, row - 1)?;
+
+                        // the shift is checked by the gate, no copy needed
+                        for i in 0..W - 1 {
+                            region.assign_advice(
+                                || format!("x_{}", row + i),
+                                advice[i],
+                                row,
+                                || terms[row + i].value().copied(),
+                            )?;
+                        }
+
+                        let value = next(&terms);
+                        terms.push(region.assign_advice(
+                            || format!("x_{}", row + W - 1),
+                            advice[W - 1],
+                            row,
+                            || value,
+                        )?);
+                    }
+
+                    Ok(terms)
+                },
+            ),
+        }
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// A step given both as a gate and natively.
+    trait Step<F: FieldExt, const W: usize> {
+        fn expr(window: [Expression<F>; W]) -> Expression<F>;
+        fn native(window: [F; W]) -> F;
+    }
+
+    #[derive(Clone, Default)]
+    struct Fibonacci;
+
+    impl<F: FieldExt> Step<F, 2> for Fibonacci {
+        fn expr([a, b]: [Expression<F>; 2]) -> Expression<F> {
+            a + b
+        }
+
+        fn native([a, b]: [F; 2]) -> F {
+            a + b
+        }
+    }
+
+    /// `x_{i+1} = x_i^2 + 1`
+    #[derive(Clone, Default)]
+    struct Square;
+
+    impl<F: FieldExt> Step<F, 1> for Square {
+        fn expr([x]: [Expression<F>; 1]) -> Expression<F> {
+            x.clone() * x + Expression::Constant(F::one())
+        }
+
+        fn native([x]: [F; 1]) -> F {
+            x.square() + F::one()
+        }
+    }
+
+    /// `x_{i+3} = x_i·x_{i+1} + x_{i+2}`
+    #[derive(Clone, Default)]
+    struct Mixed;
+
+    impl<F: FieldExt> Step<F, 3> for Mixed {
+        fn expr([a, b, c]: [Expression<F>; 3]) -> Expression<F> {
+            a * b + c
+        }
+
+        fn native([a, b, c]: [F; 3]) -> F {
+            a * b + c
+        }
+    }
+
+    /// Public inputs are the `W` seeds followed by `x_n`.
+    #[derive(Clone)]
+    struct MyCircuit<F, S, const W: usize> {
+        seeds: [Value<F>; W],
+        n: usize,
+        multi_column: bool,
+        _marker: PhantomData<S>,
+    }
+
+    #[derive(Clone, Debug)]
+    struct MyConfig<const W: usize> {
+        single: TraceConfig<W>,
+        multi: TraceConfig<W>,
+    }
+
+    impl<F: FieldExt, S: Step<F, W>, const W: usize> Circuit<F> for MyCircuit<F, S, W> {
+        type Config = MyConfig<W>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                seeds: [Value::unknown(); W],
+                n: self.n,
+                multi_column: self.multi_column,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [(); W].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+
+            MyConfig {
+                single: TraceChip::configure(
+                    meta,
+                    TraceLayout::SingleColumn(advice[0]),
+                    instance,
+                    S::expr,
+                ),
+                multi: TraceChip::configure(
+                    meta,
+                    TraceLayout::MultiColumn(advice),
+                    instance,
+                    S::expr,
+                ),
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let config = if self.multi_column {
+                config.multi
+            } else {
+                config.single
+            };
+            let chip = TraceChip::construct(config);
+
+            let terms = chip.assign(
+                layouter.namespace(|| "trace"),
+                self.seeds,
+                S::native,
+                self.n + 1,
+            )?;
+            for (row, seed) in terms.iter().take(W).enumerate() {
+                chip.expose_public(layouter.namespace(|| "seed"), seed, row)?;
+            }
+            chip.expose_public(layouter.namespace(|| "out"), terms.last().unwrap(), W)
+        }
+    }
+
+    fn run<S: Step<Fp, W> + Clone, const W: usize>(seeds: [u64; W], n: usize) -> Fp {
+        let seeds = seeds.map(Fp::from);
+        let out = iterate(seeds, S::native, n);
+
+        let mut public_input = seeds.to_vec();
+        public_input.push(out);
+
+        for multi_column in [false, true] {
+            let circuit = MyCircuit::<Fp, S, W> {
+                seeds: seeds.map(Value::known),
+                n,
+                multi_column,
+                _marker: PhantomData,
+            };
+
+            let prover = MockProver::run(5, &circuit, vec![public_input.clone()]).unwrap();
+            prover.assert_satisfied();
+
+            let mut wrong_input = public_input.clone();
+            wrong_input[W] += Fp::one();
+            let prover = MockProver::run(5, &circuit, vec![wrong_input]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        out
+    }
+
+    #[test]
+    fn trace_fibonacci() {
+        assert_eq!(run::<Fibonacci, 2>([1, 1], 9), Fp::from(55));
+    }
+
+    #[test]
+    fn trace_square() {
+        // 0, 1, 2, 5, 26, 677
+        assert_eq!(run::<Square, 1>([0], 5), Fp::from(677));
+    }
+
+    #[test]
+    fn trace_mixed() {
+        // 1, 1, 1, 2, 3, 5, 11, 26
+        assert_eq!(run::<Mixed, 3>([1, 1, 1], 7), Fp::from(26));
+    }
+}
@@ -1,4 +1,5 @@
-mod fibonacci;
-mod example_iszero;
-mod range_check;
-mod arithmetic;
+pub mod fibonacci;
+pub mod example_iszero;
+pub mod range_check;
+pub mod arithmetic;
+pub mod prove;
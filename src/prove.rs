@@ -0,0 +1,104 @@
+//! Real proving and verification with the IPA commitment scheme over the Pasta
+//! curves. `MockProver` only checks that the constraints hold, these helpers
+//! produce an actual proof and check it the way a verifier would.
+
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey,
+        SingleVerifier, VerifyingKey,
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+/// Generates the parameters and the proving key for circuits of size `2^k`.
+///
+/// Keys only depend on the shape of the circuit, so they are generated from
+/// `circuit.without_witnesses()`. The circuit must lay out the same regions
+/// whether or not its witnesses are known.
+pub fn keygen<C: Circuit<Fp>>(
+    k: u32,
+    circuit: &C,
+) -> Result<(Params<EqAffine>, ProvingKey<EqAffine>), Error> {
+    let params = Params::<EqAffine>::new(k);
+    let circuit = circuit.without_witnesses();
+
+    let vk = keygen_vk(&params, &circuit)?;
+    let pk = keygen_pk(&params, vk, &circuit)?;
+
+    Ok((params, pk))
+}
+
+/// Creates a proof for `circuit`, with one slice of public inputs per instance column.
+pub fn prove<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: C,
+    instances: &[&[Fp]],
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(params, pk, &[circuit], &[instances], OsRng, &mut transcript)?;
+
+    Ok(transcript.finalize())
+}
+
+/// Checks `proof` against the verifying key and the public inputs.
+pub fn verify(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    instances: &[&[Fp]],
+    proof: &[u8],
+) -> Result<(), Error> {
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+
+    verify_proof(params, vk, strategy, &[instances], &mut transcript)
+}
+
+/// Runs `keygen`, `prove` and `verify` end to end.
+pub fn prove_and_verify<C: Circuit<Fp>>(
+    k: u32,
+    circuit: C,
+    instances: &[&[Fp]],
+) -> Result<(), Error> {
+    let (params, pk) = keygen(k, &circuit)?;
+    let proof = prove(&params, &pk, circuit, instances)?;
+
+    verify(&params, pk.get_vk(), instances, &proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::{example1, FibonacciCircuit};
+    use halo2_proofs::circuit::Value;
+
+    #[test]
+    fn prove_fibonacci() {
+        let circuit = FibonacciCircuit::<Fp>::new(9);
+        let public_input = circuit.public_inputs(Fp::one(), Fp::one());
+
+        let (params, pk) = keygen(circuit.k(), &circuit).unwrap();
+        let proof = prove(&params, &pk, circuit, &[&public_input]).unwrap();
+        assert!(verify(&params, pk.get_vk(), &[&public_input], &proof).is_ok());
+
+        // the same proof does not verify against another output
+        let mut wrong_input = public_input;
+        wrong_input[2] += Fp::one();
+        assert!(verify(&params, pk.get_vk(), &[&wrong_input], &proof).is_err());
+    }
+
+    #[test]
+    fn prove_private_seeds() {
+        // keygen runs on `without_witnesses`, where the seeds are unknown
+        let circuit = example1::MyCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        let public_input = [Fp::one(), Fp::one(), Fp::from(55)];
+
+        assert!(prove_and_verify(4, circuit, &[&public_input]).is_ok());
+    }
+}
@@ -1 +1,7 @@
-mod example1;
\ No newline at end of file
+mod chip;
+pub mod example1;
+pub mod example2;
+pub mod example3;
+pub mod interval;
+
+pub use chip::{RangeCheckChip, RangeCheckConfig, RangeCheckStrategy, LIMB_BITS, MAX_LOOKUP_RANGE};
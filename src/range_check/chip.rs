@@ -0,0 +1,548 @@
+/// A range check `v < range` for a range only known at configuration time, picking
+/// the cheapest of the three helpers:
+///
+///  - the `range_check` expression gate of example1, while its degree fits within
+///    what the circuit already pays for, and at least what a lookup would cost;
+///  - a single lookup in a table of `0..range`, as in example2, up to
+///    `MAX_LOOKUP_RANGE`;
+///  - a running-sum decomposition into `LIMB_BITS`-bit limbs, as in example3, for
+///    anything larger. For a range that is not a power of two, `v + 2^n - range`
+///    is decomposed into `n` bits as well, right below the running sum of `v`:
+///
+///  value              | limb | q_decompose | q_shift
+///    v = z_0          | l_0  |      1      |    1
+///    ...              | ...  |     ...     |
+///    z_L = 0          |      |             |
+///    v + 2^n - range  | l'_0 |      1      |
+///    ...              | ...  |     ...     |
+///    z'_L = 0         |      |             |
+///
+/// The range is a field element, so anything up to `2^(F::NUM_BITS - 1)` can be
+/// checked.
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+use super::example3::DecomposeConfig;
+
+/// Bits per limb of the decomposition strategy.
+pub const LIMB_BITS: usize = 8;
+
+/// Largest range checked with a single lookup, the table takes `MAX_LOOKUP_RANGE` rows.
+pub const MAX_LOOKUP_RANGE: u64 = 1 << 16;
+
+/// Degree of a lookup of `q·v`, the expression gate is chosen when it is no worse.
+const LOOKUP_DEGREE: usize = 5;
+
+/// v * (1-v) * (2-v) * ....... * (R - 1 - v), zero iff `v < R`
+pub(crate) fn range_check<F: FieldExt>(range: usize, value: Expression<F>) -> Expression<F> {
+    (0..range).fold(Expression::Constant(F::one()), |expr, i| {
+        expr * (Expression::Constant(F::from(i as u64)) - value.clone())
+    })
+}
+
+/// Loads `0..range` into `column`.
+fn load_range<F: FieldExt>(
+    layouter: &mut impl Layouter<F>,
+    column: TableColumn,
+    range: usize,
+) -> Result<(), Error> {
+    layouter.assign_table(
+        || "load range-check table",
+        |mut table| {
+            for value in 0..range {
+                table.assign_cell(
+                    || "value",
+                    column,
+                    value,
+                    || Value::known(F::from(value as u64)),
+                )?;
+            }
+            Ok(())
+        },
+    )
+}
+
+/// `x` as an integer, if it fits in 128 bits.
+fn to_u128<F: FieldExt>(x: F) -> Option<u128> {
+    let lower = x.get_lower_128();
+    (F::from_u128(lower) == x).then_some(lower)
+}
+
+/// Number of bits of `x` as an integer below the modulus.
+fn bit_len<F: FieldExt>(x: F) -> usize {
+    let repr = x.to_repr();
+    repr.as_ref()
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, byte)| **byte != 0)
+        .map_or(0, |(i, byte)| 8 * i + 8 - byte.leading_zeros() as usize)
+}
+
+#[derive(Clone, Debug)]
+pub enum RangeCheckStrategy<F: FieldExt> {
+    Expression {
+        q_range_check: Selector,
+    },
+    Lookup {
+        q_lookup: Selector,
+        table: TableColumn,
+    },
+    Decompose {
+        decompose: DecomposeConfig<F, LIMB_BITS>,
+        num_bits: usize,
+        /// Set for ranges that are not a power of two.
+        q_shift: Option<Selector>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct RangeCheckConfig<F: FieldExt> {
+    pub value: Column<Advice>,
+    pub range: F,
+    pub strategy: RangeCheckStrategy<F>,
+}
+
+pub struct RangeCheckChip<F: FieldExt> {
+    config: RangeCheckConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> RangeCheckChip<F> {
+    pub fn construct(config: RangeCheckConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Checks values of `value` against `range`, any extra columns are allocated here.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        range: F,
+    ) -> RangeCheckConfig<F> {
+        let budget = meta.degree().max(LOOKUP_DEGREE) as u128;
+
+        // the gate has degree range + 1: the selector and one factor per value
+        match to_u128(range) {
+            Some(r) if r < budget => Self::configure_expression(meta, value, range),
+            Some(r) if r <= MAX_LOOKUP_RANGE as u128 => Self::configure_lookup(meta, value, range),
+            _ => Self::configure_decompose(meta, value, range),
+        }
+    }
+
+    /// Checks `value` with the `range_check` expression gate, whatever its degree.
+    pub fn configure_expression(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        range: F,
+    ) -> RangeCheckConfig<F> {
+        let r = to_u128(range).expect("range too large for the expression gate") as usize;
+        assert!(r > 0, "empty range");
+
+        let q_range_check = meta.selector();
+        meta.create_gate("range check", |meta| {
+            let q = meta.query_selector(q_range_check);
+            let value = meta.query_advice(value, Rotation::cur());
+
+            Constraints::with_selector(q, [("range check", range_check(r, value))])
+        });
+
+        RangeCheckConfig {
+            value,
+            range,
+            strategy: RangeCheckStrategy::Expression { q_range_check },
+        }
+    }
+
+    /// Checks `value` with a lookup in a table of `0..range`.
+    pub fn configure_lookup(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        range: F,
+    ) -> RangeCheckConfig<F> {
+        let r = to_u128(range).expect("range too large for a lookup table");
+        assert!(r > 0, "empty range");
+
+        // Lookups need a complex selector, it ends up inside the input expression.
+        // A disabled row looks up 0, which is always in the table
+        let q_lookup = meta.complex_selector();
+        let table = meta.lookup_table_column();
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_lookup);
+            let value = meta.query_advice(value, Rotation::cur());
+
+            vec![(q * value, table)]
+        });
+
+        RangeCheckConfig {
+            value,
+            range,
+            strategy: RangeCheckStrategy::Lookup { q_lookup, table },
+        }
+    }
+
+    /// Checks `value` by decomposing it into `LIMB_BITS`-bit limbs.
+    pub fn configure_decompose(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        range: F,
+    ) -> RangeCheckConfig<F> {
+        assert!(range != F::zero(), "empty range");
+
+        let num_bits = bit_len(range - F::one());
+        let is_power_of_two = F::from(2).pow_vartime([num_bits as u64]) == range;
+        // v + 2^n - range stays below 2^(n + 1), neither may wrap around
+        assert!(
+            num_bits + (!is_power_of_two as usize) < F::NUM_BITS as usize,
+            "the range does not fit in the field"
+        );
+
+        let limb = meta.advice_column();
+        let constant = meta.fixed_column();
+        let decompose = DecomposeConfig::configure(meta, value, limb, constant);
+
+        let q_shift = (!is_power_of_two).then(|| {
+            let q_shift = meta.selector();
+            let offset = F::from(2).pow_vartime([num_bits as u64]) - range;
+            // the shifted value starts right after z_L of v
+            let rotation = Rotation((num_bits.div_ceil(LIMB_BITS) + 1) as i32);
+            meta.create_gate("shift", |meta| {
+                let q = meta.query_selector(q_shift);
+                let v = meta.query_advice(value, Rotation::cur());
+                let shifted = meta.query_advice(value, rotation);
+
+                Constraints::with_selector(
+                    q,
+                    [(
+                        "v + 2^n - range",
+                        shifted - v - Expression::Constant(offset),
+                    )],
+                )
+            });
+            q_shift
+        });
+
+        RangeCheckConfig {
+            value,
+            range,
+            strategy: RangeCheckStrategy::Decompose {
+                decompose,
+                num_bits,
+                q_shift,
+            },
+        }
+    }
+
+    /// Loads the lookup table of the chosen strategy, if any, once per circuit.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        match &self.config.strategy {
+            RangeCheckStrategy::Expression { .. } => Ok(()),
+            RangeCheckStrategy::Lookup { table, .. } => {
+                load_range(layouter, *table, self.config.range.get_lower_128() as usize)
+            }
+            RangeCheckStrategy::Decompose { decompose, .. } => decompose.load_table(layouter),
+        }
+    }
+
+    /// Rows of the `value` column taken by one check.
+    pub fn rows(&self) -> usize {
+        match &self.config.strategy {
+            RangeCheckStrategy::Expression { .. } | RangeCheckStrategy::Lookup { .. } => 1,
+            RangeCheckStrategy::Decompose {
+                num_bits, q_shift, ..
+            } => {
+                let rows = num_bits.div_ceil(LIMB_BITS) + 1;
+                if q_shift.is_some() {
+                    2 * rows
+                } else {
+                    rows
+                }
+            }
+        }
+    }
+
+    /// Assigns `value` and checks it is below the range.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "Assign value",
+            |mut region| self.assign_in_region(&mut region, 0, value),
+        )
+    }
+
+    /// Assigns `value` at `offset` of a region laid out by the caller and checks
+    /// it is below the range. The check takes `rows()` rows of the `value` column
+    /// from `offset` on, and the limb column next to them for a decomposition.
+    pub fn assign_in_region(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        match &self.config.strategy {
+            RangeCheckStrategy::Expression { q_range_check: q }
+            | RangeCheckStrategy::Lookup { q_lookup: q, .. } => {
+                q.enable(region, offset)?;
+                region.assign_advice(|| "value", self.config.value, offset, || value)
+            }
+            RangeCheckStrategy::Decompose { num_bits, .. } => {
+                let offset_value = F::from(2).pow_vartime([*num_bits as u64]) - self.config.range;
+                let shifted = value + Value::known(offset_value);
+                self.assign_decomposed(region, offset, value, shifted)
+            }
+        }
+    }
+
+    /// Lays out the decomposition of `value` and, for a range that is not a
+    /// power of two, of `shifted`, which should be `value + 2^n - range`.
+    ///
+    /// A value that does not fit leaves a non-zero `z_L`, which fails verification.
+    fn assign_decomposed(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+        shifted: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let (decompose, num_bits, q_shift) = match &self.config.strategy {
+            RangeCheckStrategy::Decompose {
+                decompose,
+                num_bits,
+                q_shift,
+            } => (decompose, *num_bits, q_shift),
+            _ => return Err(Error::Synthesis),
+        };
+        let num_limbs = num_bits.div_ceil(LIMB_BITS);
+        let short = match num_bits % LIMB_BITS {
+            0 => None,
+            r => Some(F::from(1 << (LIMB_BITS - r))),
+        };
+
+        let mut running_sum = |offset, value, name| -> Result<AssignedCell<F, F>, Error> {
+            let z_0 = region.assign_advice(|| name, self.config.value, offset, || value)?;
+            let (_, z) = decompose.assign_running_sum(region, offset, &z_0, num_limbs, short)?;
+            region.constrain_constant(z.cell(), F::zero())?;
+            Ok(z_0)
+        };
+
+        let v = running_sum(offset, value, "value")?;
+        if let Some(q_shift) = q_shift {
+            running_sum(offset + num_limbs + 1, shifted, "v + 2^n - range")?;
+            q_shift.enable(region, offset)?;
+        }
+        Ok(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    use super::*;
+
+    /// Checks a value against `RANGE·2^SHIFT`.
+    #[derive(Default)]
+    struct MyCircuit<F: FieldExt, const RANGE: u128, const SHIFT: u64 = 0> {
+        value: Value<F>,
+    }
+
+    fn range<F: FieldExt, const RANGE: u128, const SHIFT: u64>() -> F {
+        F::from_u128(RANGE) * F::from(2).pow_vartime([SHIFT])
+    }
+
+    impl<F: FieldExt, const RANGE: u128, const SHIFT: u64> Circuit<F> for MyCircuit<F, RANGE, SHIFT> {
+        type Config = RangeCheckConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckChip::configure(meta, value, range::<F, RANGE, SHIFT>())
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = RangeCheckChip::construct(config);
+            chip.load(&mut layouter)?;
+            chip.assign(layouter.namespace(|| "Assign value"), self.value)?;
+
+            Ok(())
+        }
+    }
+
+    fn run<const RANGE: u128, const SHIFT: u64>(k: u32, value: Fp) -> MockProver<Fp> {
+        let circuit = MyCircuit::<Fp, RANGE, SHIFT> {
+            value: Value::known(value),
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_strategy() {
+        let strategy = |range| {
+            let mut meta = ConstraintSystem::<Fp>::default();
+            let value = meta.advice_column();
+            match RangeCheckChip::configure(&mut meta, value, Fp::from(range)).strategy {
+                RangeCheckStrategy::Expression { .. } => "expression",
+                RangeCheckStrategy::Lookup { .. } => "lookup",
+                RangeCheckStrategy::Decompose { .. } => "decompose",
+            }
+        };
+
+        assert_eq!(strategy(2), "expression");
+        assert_eq!(strategy(4), "expression");
+        assert_eq!(strategy(5), "lookup");
+        assert_eq!(strategy(MAX_LOOKUP_RANGE), "lookup");
+        assert_eq!(strategy(MAX_LOOKUP_RANGE + 1), "decompose");
+
+        // a circuit that already pays for a degree 8 gate uses the gate up to 7
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let value = meta.advice_column();
+        RangeCheckChip::configure(&mut meta, value, Fp::from(MAX_LOOKUP_RANGE + 1));
+        let config = RangeCheckChip::configure(&mut meta, value, Fp::from(7));
+        assert!(matches!(config.strategy, RangeCheckStrategy::Lookup { .. }));
+        let q = meta.selector();
+        meta.create_gate("degree 8", |meta| {
+            let v = meta.query_advice(value, Rotation::cur());
+            vec![meta.query_selector(q) * range_check(7, v)]
+        });
+        let config = RangeCheckChip::configure(&mut meta, value, Fp::from(7));
+        assert!(matches!(
+            config.strategy,
+            RangeCheckStrategy::Expression { .. }
+        ));
+    }
+
+    #[test]
+    fn test_range_check_chip() {
+        // expression gate
+        run::<3, 0>(4, Fp::from(2)).assert_satisfied();
+        assert!(run::<3, 0>(4, Fp::from(3)).verify().is_err());
+
+        // lookup
+        run::<1000, 0>(10, Fp::from(999)).assert_satisfied();
+        assert!(run::<1000, 0>(10, Fp::from(1000)).verify().is_err());
+
+        // decomposition, 2^40 and 10^12 < 2^40
+        run::<1, 40>(9, Fp::from((1 << 40) - 1)).assert_satisfied();
+        assert!(run::<1, 40>(9, Fp::from(1 << 40)).verify().is_err());
+        run::<1_000_000_000_000, 0>(9, Fp::from(999_999_999_999)).assert_satisfied();
+        assert!(run::<1_000_000_000_000, 0>(9, Fp::from(1_000_000_000_000))
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn test_wide_ranges() {
+        let pow2 = |n| Fp::from(2).pow_vartime([n]);
+
+        // 128 bits
+        run::<1, 128>(9, pow2(128) - Fp::one()).assert_satisfied();
+        assert!(run::<1, 128>(9, pow2(128)).verify().is_err());
+
+        // 254 bits, one below the modulus of Fp
+        run::<1, 254>(9, pow2(254) - Fp::one()).assert_satisfied();
+        assert!(run::<1, 254>(9, pow2(254)).verify().is_err());
+        assert!(run::<1, 254>(9, -Fp::one()).verify().is_err());
+
+        // 3·2^200, with the shifted value
+        let range = Fp::from(3) * pow2(200);
+        run::<3, 200>(10, range - Fp::one()).assert_satisfied();
+        assert!(run::<3, 200>(10, range).verify().is_err());
+    }
+
+    /// Checks `value` against `10^12` with a hand-assigned `v + 2^40 - 10^12`.
+    #[derive(Default)]
+    struct ForgedShiftCircuit<F> {
+        value: Value<F>,
+        shifted: Value<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for ForgedShiftCircuit<F> {
+        type Config = RangeCheckConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckChip::configure(meta, value, F::from(1_000_000_000_000))
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = RangeCheckChip::construct(config);
+            chip.load(&mut layouter)?;
+            layouter.assign_region(
+                || "Assign value",
+                |mut region| chip.assign_decomposed(&mut region, 0, self.value, self.shifted),
+            )?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_forged_shift() {
+        // 10^12 + 5 fits in 40 bits, v + 2^40 - 10^12 = 2^40 + 5 does not,
+        // so the prover claims 5 instead
+        let value = Fp::from(1_000_000_000_005);
+        let circuit = ForgedShiftCircuit {
+            value: Value::known(value),
+            shifted: Value::known(Fp::from(5)),
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        match &failures[0] {
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location,
+                ..
+            } => {
+                assert_eq!(
+                    *constraint,
+                    ((1, "shift").into(), 0, "v + 2^n - range").into()
+                );
+                assert_eq!(
+                    *location,
+                    FailureLocation::InRegion {
+                        region: (1, "Assign value").into(),
+                        offset: 0
+                    }
+                );
+            }
+            failure => panic!("unexpected failure {}", failure),
+        }
+
+        // the honest shifted value fails its own decomposition instead
+        let circuit = ForgedShiftCircuit {
+            value: Value::known(value),
+            shifted: Value::known(value + Fp::from(1 << 40) - Fp::from(1_000_000_000_000)),
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -2,76 +2,68 @@
 ///
 ///  value | q_range_check
 ///     v  |        1
-
-use std::marker::PhantomData;
-
 use halo2_proofs::{
-    arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation
+    arithmetic::FieldExt, circuit::*, plonk::*
 };
 
+use super::{RangeCheckChip, RangeCheckConfig};
+
 #[derive(Clone, Debug)]
-struct RnageCheckConfig<F: FieldExt, const RANGE: usize> {
-    value: Column<Advice>,
-    q_range_check: Selector,
-    _marker: PhantomData<F>,
+pub struct RnageCheckConfig<F: FieldExt, const RANGE: usize> {
+    pub range_check: RangeCheckConfig<F>,
 }
 
 impl <F: FieldExt, const RANGE: usize> RnageCheckConfig<F, RANGE> {        
-    fn configure(
+    pub fn configure(
         meta: &mut ConstraintSystem<F>,
         value: Column<Advice>
     ) -> Self {
-        // Toggle the range check constaint
-        let q_range_check = meta.selector();
-
-        const RANGE: usize = 100;
-
         // Range check gate
         // For a value v and a range R, check that v < R
         // v * (1-v) * (2-v) * ....... * (R - 1 - v)
-        meta.create_gate("range_check", |meta| {
-            let q = meta.query_selector(q_range_check);
-            let value = meta.query_advice(value, Rotation::cur());
-
-            let range_check = |range: usize, value: Expression<F>| {
-                (0..range).fold(value.clone(), |expr, i|{
-                    expr * (Expression::Constant(F::from(i as u64)) - value.clone())
-                })
-            };
-
-            Constraints::with_selector(q, [("range check", range_check(RANGE, value))])
-        });
+        let range_check = RangeCheckChip::configure_expression(meta, value, F::from(RANGE as u64));
 
-        Self {
-            value,
-            q_range_check,
-            _marker: PhantomData,
-        }
-      
+        Self { range_check }
     }
 
-    fn assign(
+    pub fn assign(
         &self,
         mut layouter: impl Layouter<F>,
         value: Value<F>,
-    ) -> Result<(), Error> {
-        layouter.assign_region(|| "Assign Value", |mut region| {
-             // enable q_range_check
-        let _ = self.q_range_check.enable(&mut region, 0);
-
-        // Assign given value
-        let _ = region.assign_advice(|| "value", self.value, 0, || value);
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let chip = RangeCheckChip::construct(self.range_check.clone());
+        layouter.assign_region(|| "Assign value", |mut region| {
+            // enable q_range_check and assign the given value
+            chip.assign_in_region(&mut region, 0, value)
+        })
+    }
 
-        Ok(())
+    /// Checks all `values` in a single region, one row each
+    ///
+    ///  value | q_range_check
+    ///   v_0  |        1
+    ///   v_1  |        1
+    ///   ...  |       ...
+    pub fn assign_many(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let chip = RangeCheckChip::construct(self.range_check.clone());
+        layouter.assign_region(|| "Assign Values", |mut region| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(offset, value)| chip.assign_in_region(&mut region, offset, *value))
+                .collect()
         })
     }
-    
 }
 
 #[cfg(test)]
 mod tests {
     use halo2_proofs::{
-        arithmetic::FieldExt, circuit::{self, *}, dev::{FailureLocation, MockProver, VerifyFailure}, pasta::Fp, plonk::*, poly::Rotation
+        arithmetic::FieldExt, circuit::*, dev::{FailureLocation, MockProver, VerifyFailure}, pasta::Fp, plonk::*
     };
 use super::*;
 #[derive(Default)]
@@ -109,7 +101,7 @@ fn test_range_check() {
     // Successful cases
     for i in 0..RANGE{
         let circuit = MyCircuit::<Fp, RANGE> {
-            value: Value::known(Fp::from(i as u64).into()),
+            value: Value::known(Fp::from(i as u64)),
         };
 
     let prover = MockProver::run(k, &circuit, vec![]).unwrap();
@@ -118,7 +110,7 @@ fn test_range_check() {
     // Out-of-range `value = 8`
     {
         let circuit = MyCircuit::<Fp, RANGE> {
-            value: Value::known(Fp::from(RANGE as u64).into()),
+            value: Value::known(Fp::from(RANGE as u64)),
         };
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         assert_eq!(
@@ -134,5 +126,84 @@ fn test_range_check() {
         );
     }
 }
+
+#[test]
+fn test_range_check_real_proof() {
+    let k = 4;
+    const RANGE: usize = 8; // 3-bit value
+
+    let circuit = MyCircuit::<Fp, RANGE> {
+        value: Value::known(Fp::from(RANGE as u64 - 1)),
+    };
+    assert!(crate::prove::prove_and_verify(k, circuit, &[]).is_ok());
+}
+
+#[derive(Default)]
+struct ManyCircuit<F: FieldExt, const RANGE: usize> {
+    values: Vec<Value<F>>,
+}
+
+impl<F: FieldExt, const RANGE: usize> Circuit<F> for ManyCircuit<F, RANGE> {
+    type Config = (RnageCheckConfig<F, RANGE>, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![Value::unknown(); self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(value);
+        meta.enable_equality(instance);
+        (RnageCheckConfig::configure(meta, value), instance)
+    }
+
+    fn synthesize(&self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<F>
+     ) -> Result<(), Error> {
+        let cells = config.assign_many(layouter.namespace(|| "Assign Values"), &self.values)?;
+
+        // the checked values can be copied elsewhere
+        for (row, cell) in cells.iter().enumerate() {
+            layouter.constrain_instance(cell.cell(), instance, row)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_range_check_many() {
+    let k = 4;
+    const RANGE: usize = 8; // 3-bit value
+
+    let values: Vec<_> = [3, 0, 7, 5, 1].map(|v: u64| Fp::from(v)).to_vec();
+    let circuit = ManyCircuit::<Fp, RANGE> {
+        values: values.iter().map(|v| Value::known(*v)).collect(),
+    };
+    let prover = MockProver::run(k, &circuit, vec![values.clone()]).unwrap();
+    prover.assert_satisfied();
+
+    // Out-of-range `value = 8` on the third row
+    let mut values = values;
+    values[2] = Fp::from(RANGE as u64);
+    let circuit = ManyCircuit::<Fp, RANGE> {
+        values: values.iter().map(|v| Value::known(*v)).collect(),
+    };
+    let prover = MockProver::run(k, &circuit, vec![values]).unwrap();
+    assert_eq!(
+        prover.verify(),
+        Err(vec![VerifyFailure::ConstraintNotSatisfied {
+            constraint: ((0, "range check").into(), 0, "range check").into(),
+            location: FailureLocation::InRegion {
+                region: (0, "Assign Values").into(),
+                offset: 2
+            },
+            cell_values: vec![(((Any::Advice, 0).into(), 0).into(), "0x8".to_string())]
+        }])
+    );
+}
 }
-    
\ No newline at end of file
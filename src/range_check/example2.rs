@@ -0,0 +1,128 @@
+/// This helper checks that the value witnessed in a given cell is within a given range,
+/// by looking it up in a table of `0..RANGE`. Unlike the `range_check` gate of example1,
+/// the degree does not grow with `RANGE`, which can go up to `2^16` and beyond as long as
+/// the table fits in `2^k` rows.
+///
+///  value | q_lookup |  table_value  |
+///    v   |    1     |       0       |
+///        |          |       1       |
+///        |          |      ...      |
+///        |          |   RANGE - 1   |
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
+
+use super::{RangeCheckChip, RangeCheckConfig};
+
+#[derive(Clone, Debug)]
+pub struct RangeLookupConfig<F: FieldExt, const RANGE: usize> {
+    pub range_check: RangeCheckConfig<F>,
+}
+
+impl<F: FieldExt, const RANGE: usize> RangeLookupConfig<F, RANGE> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self {
+        let range_check = RangeCheckChip::configure_lookup(meta, value, F::from(RANGE as u64));
+
+        Self { range_check }
+    }
+
+    /// Loads the table, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        RangeCheckChip::construct(self.range_check.clone()).load(layouter)
+    }
+
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let chip = RangeCheckChip::construct(self.range_check.clone());
+        layouter.assign_region(
+            || "Assign value for lookup range check",
+            |mut region| chip.assign_in_region(&mut region, 0, value),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MyCircuit<F: FieldExt, const RANGE: usize> {
+        value: Value<F>,
+    }
+
+    impl<F: FieldExt, const RANGE: usize> Circuit<F> for MyCircuit<F, RANGE> {
+        type Config = RangeLookupConfig<F, RANGE>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeLookupConfig::configure(meta, value)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load_table(&mut layouter)?;
+            config.assign(layouter.namespace(|| "Assign value"), self.value)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_range_lookup() {
+        let k = 17;
+        const RANGE: usize = 1 << 16;
+
+        // Successful cases
+        for i in [0, 1, 255, 256, RANGE - 1] {
+            let circuit = MyCircuit::<Fp, RANGE> {
+                value: Value::known(Fp::from(i as u64)),
+            };
+
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+
+        // Out-of-range `value = RANGE`
+        {
+            let circuit = MyCircuit::<Fp, RANGE> {
+                value: Value::known(Fp::from(RANGE as u64)),
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_eq!(
+                prover.verify(),
+                Err(vec![VerifyFailure::Lookup {
+                    lookup_index: 0,
+                    location: FailureLocation::InRegion {
+                        region: (1, "Assign value for lookup range check").into(),
+                        offset: 0
+                    }
+                }])
+            );
+        }
+    }
+
+    #[test]
+    fn test_range_lookup_real_proof() {
+        let k = 9;
+        const RANGE: usize = 256;
+
+        let circuit = MyCircuit::<Fp, RANGE> {
+            value: Value::known(Fp::from(RANGE as u64 - 1)),
+        };
+        assert!(crate::prove::prove_and_verify(k, circuit, &[]).is_ok());
+    }
+}
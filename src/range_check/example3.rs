@@ -0,0 +1,270 @@
+/// This helper checks that the value witnessed in a given cell fits in `num_bits` bits,
+/// for `num_bits` too large for the `range_check` gate of example1 or for a single lookup.
+/// The value is decomposed into `K`-bit limbs with a running sum, each limb is looked up
+/// in a table of `0..2^K`:
+///
+///    z     |  limb   |   short   | q_decompose | table_value
+/// v = z_0  |   l_0   |     0     |      1      |      0
+///   z_1    |   l_1   |     0     |      1      |      1
+///   ...    |   ...   |    ...    |     ...     |     ...
+/// z_{L-1}  | l_{L-1} | 2^(K - r) |      1      |   2^K - 1
+///   z_L    |         |           |             |
+///
+/// with `z_i = 2^K·z_{i+1} + l_i` and `z_L = 0`. When `num_bits = (L - 1)·K + r` is
+/// not a multiple of `K`, the top limb is also looked up shifted by `short`, which
+/// keeps it below `2^r`.
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Clone, Debug)]
+pub struct DecomposeConfig<F: FieldExt, const K: usize> {
+    pub z: Column<Advice>,
+    pub limb: Column<Advice>,
+    pub short: Column<Fixed>,
+    pub q_decompose: Selector,
+    pub table: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const K: usize> DecomposeConfig<F, K> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        z: Column<Advice>,
+        limb: Column<Advice>,
+        constant: Column<Fixed>,
+    ) -> Self {
+        let q_decompose = meta.complex_selector();
+        let short = meta.fixed_column();
+        let table = meta.lookup_table_column();
+
+        meta.enable_equality(z);
+        meta.enable_equality(limb);
+        meta.enable_constant(constant);
+
+        meta.create_gate("decompose", |meta| {
+            let q = meta.query_selector(q_decompose);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+            let l = meta.query_advice(limb, Rotation::cur());
+
+            let radix = Expression::Constant(F::from(1 << K));
+            Constraints::with_selector(q, [("running sum", z_cur - (radix * z_next + l))])
+        });
+
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_decompose);
+            let l = meta.query_advice(limb, Rotation::cur());
+
+            vec![(q * l, table)]
+        });
+
+        // `short` is zero outside of top limbs, where 0 is looked up
+        meta.lookup(|meta| {
+            let short = meta.query_fixed(short, Rotation::cur());
+            let l = meta.query_advice(limb, Rotation::cur());
+
+            vec![(short * l, table)]
+        });
+
+        Self {
+            z,
+            limb,
+            short,
+            q_decompose,
+            table,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the table of `0..2^K`, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load limb table",
+            |mut table| {
+                for value in 0..1 << K {
+                    table.assign_cell(
+                        || "value",
+                        self.table,
+                        value,
+                        || Value::known(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Constrains `value` to fit in `num_bits` bits and returns its limbs, least
+    /// significant first. A known value that does not fit is a `Error::Synthesis`.
+    pub fn decompose(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let num_limbs = num_bits.div_ceil(K);
+        let short = match num_bits % K {
+            0 => None,
+            r => Some(F::from(1 << (K - r))),
+        };
+        layouter.assign_region(
+            || "decompose",
+            |mut region| {
+                let z_0 = value.copy_advice(|| "z_0", &mut region, self.z, 0)?;
+                let (limbs, z) = self.assign_running_sum(&mut region, 0, &z_0, num_limbs, short)?;
+
+                // whatever is left over did not fit in num_bits bits
+                z.value().error_if_known_and(|z| **z != F::zero())?;
+                if let (Some(_), Some(top)) = (short, limbs.last()) {
+                    top.value()
+                        .error_if_known_and(|top| top.get_lower_128() >> (num_bits % K) != 0)?;
+                }
+                region.constrain_constant(z.cell(), F::zero())?;
+
+                Ok(limbs)
+            },
+        )
+    }
+
+    /// Lays out `num_limbs` steps of the running sum from `z_0`, which must sit in the
+    /// `z` column at `offset`, with `short` on the top limb. Returns the limbs and `z_L`,
+    /// which is left unconstrained.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn assign_running_sum(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        z_0: &AssignedCell<F, F>,
+        num_limbs: usize,
+        short: Option<F>,
+    ) -> Result<(Vec<AssignedCell<F, F>>, AssignedCell<F, F>), Error> {
+        let radix_inv = F::from(1 << K).invert().unwrap();
+        let mut z = z_0.clone();
+        let mut limbs = Vec::with_capacity(num_limbs);
+
+        for i in 0..num_limbs {
+            let row = offset + i;
+            self.q_decompose.enable(region, row)?;
+
+            let shift = match short {
+                Some(shift) if i == num_limbs - 1 => shift,
+                _ => F::zero(),
+            };
+            region.assign_fixed(|| "short", self.short, row, || Value::known(shift))?;
+
+            let limb = z
+                .value()
+                .map(|z| F::from_u128(z.get_lower_128() & ((1 << K) - 1)));
+            limbs.push(region.assign_advice(|| "limb", self.limb, row, || limb)?);
+
+            let next = (z.value().copied() - limb) * Value::known(radix_inv);
+            z = region.assign_advice(|| "z", self.z, row + 1, || next)?;
+        }
+
+        Ok((limbs, z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    const K: usize = 8;
+
+    #[derive(Default)]
+    struct MyCircuit<F: FieldExt, const NUM_BITS: usize> {
+        value: Value<F>,
+    }
+
+    impl<F: FieldExt, const NUM_BITS: usize> Circuit<F> for MyCircuit<F, NUM_BITS> {
+        type Config = (DecomposeConfig<F, K>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let z = meta.advice_column();
+            let limb = meta.advice_column();
+            let constant = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            (
+                DecomposeConfig::configure(meta, z, limb, constant),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load_table(&mut layouter)?;
+
+            let value = layouter.assign_region(
+                || "Assign value",
+                |mut region| region.assign_advice(|| "value", config.z, 0, || self.value),
+            )?;
+            let limbs = config.decompose(layouter.namespace(|| "decompose"), &value, NUM_BITS)?;
+
+            // the limbs can be reused, expose them
+            for (row, limb) in limbs.iter().enumerate() {
+                layouter.constrain_instance(limb.cell(), instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn limbs(value: u128, num_limbs: usize) -> Vec<Fp> {
+        (0..num_limbs)
+            .map(|i| Fp::from_u128((value >> (i * K)) & 0xff))
+            .collect()
+    }
+
+    #[test]
+    fn test_decompose() {
+        let k = 9;
+
+        // 64 bits, a multiple of K
+        let value = u64::MAX as u128;
+        let circuit = MyCircuit::<Fp, 64> {
+            value: Value::known(Fp::from_u128(value)),
+        };
+        let prover = MockProver::run(k, &circuit, vec![limbs(value, 8)]).unwrap();
+        prover.assert_satisfied();
+
+        // 100 bits, with a top limb of 4 bits
+        let value = (1 << 100) - 1;
+        let circuit = MyCircuit::<Fp, 100> {
+            value: Value::known(Fp::from_u128(value)),
+        };
+        let prover = MockProver::run(k, &circuit, vec![limbs(value, 13)]).unwrap();
+        prover.assert_satisfied();
+
+        // 2^100 does not fit
+        let circuit = MyCircuit::<Fp, 100> {
+            value: Value::known(Fp::from_u128(1 << 100)),
+        };
+        assert!(matches!(
+            MockProver::run(k, &circuit, vec![limbs(0, 13)]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn test_decompose_real_proof() {
+        let k = 9;
+
+        let value = 0xdead_beef_u128;
+        let circuit = MyCircuit::<Fp, 36> {
+            value: Value::known(Fp::from_u128(value)),
+        };
+        assert!(crate::prove::prove_and_verify(k, circuit, &[&limbs(value, 5)]).is_ok());
+    }
+}
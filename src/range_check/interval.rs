@@ -0,0 +1,281 @@
+/// This helper checks that the value witnessed in a given cell lies in `[lo, hi)`, with
+/// bounds that are constants or copied from the instance column. Both `v - lo` and
+/// `hi - 1 - v` are checked below `2^NUM_BITS` by a `RangeCheckChip`, which holds for
+/// `hi - lo` up to `2^NUM_BITS`:
+///
+/// value | lo | hi |     d           | q_lo | q_hi | range check
+///   v   | lo |    | v - lo          |  1   |      |  from here
+///       |    |    | ...             |      |      |
+///
+///   v   |    | hi | hi - 1 - v      |      |  1   |  from here
+///       |    |    | ...             |      |      |
+///
+/// A value out of bounds fails the range check in the "v - lo" or "hi - 1 - v" region.
+use std::marker::PhantomData;
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+use super::{RangeCheckChip, RangeCheckConfig};
+
+/// Where a bound of the interval comes from.
+#[derive(Clone, Copy, Debug)]
+pub enum Bound<F> {
+    Constant(F),
+    /// A row of the instance column.
+    Instance(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct IntervalConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub value: Column<Advice>,
+    pub lo: Column<Advice>,
+    pub hi: Column<Advice>,
+    pub instance: Column<Instance>,
+    /// Checks the differences below `2^NUM_BITS`.
+    pub range_check: RangeCheckConfig<F>,
+    pub q_lo: Selector,
+    pub q_hi: Selector,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> IntervalConfig<F, NUM_BITS> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        instance: Column<Instance>,
+    ) -> Self {
+        assert!(
+            NUM_BITS < F::NUM_BITS as usize - 1,
+            "the differences must not wrap around"
+        );
+
+        let lo = meta.advice_column();
+        let hi = meta.advice_column();
+        let d = meta.advice_column();
+        // for constant bounds
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+        let range_check =
+            RangeCheckChip::configure(meta, d, F::from(2).pow_vartime([NUM_BITS as u64]));
+
+        for column in [value, lo, hi] {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+
+        let q_lo = meta.selector();
+        let q_hi = meta.selector();
+
+        meta.create_gate("v - lo", |meta| {
+            let q_lo = meta.query_selector(q_lo);
+            let v = meta.query_advice(value, Rotation::cur());
+            let lo = meta.query_advice(lo, Rotation::cur());
+            let d = meta.query_advice(d, Rotation::cur());
+
+            Constraints::with_selector(q_lo, [("d = v - lo", d - (v - lo))])
+        });
+
+        meta.create_gate("hi - 1 - v", |meta| {
+            let q_hi = meta.query_selector(q_hi);
+            let v = meta.query_advice(value, Rotation::cur());
+            let hi = meta.query_advice(hi, Rotation::cur());
+            let d = meta.query_advice(d, Rotation::cur());
+
+            let one = Expression::Constant(F::one());
+            Constraints::with_selector(q_hi, [("d = hi - 1 - v", d - (hi - one - v))])
+        });
+
+        Self {
+            value,
+            lo,
+            hi,
+            instance,
+            range_check,
+            q_lo,
+            q_hi,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the table of the range check, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        RangeCheckChip::construct(self.range_check.clone()).load(layouter)
+    }
+
+    /// Assigns `value` and checks `lo <= value < hi`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+        lo: Bound<F>,
+        hi: Bound<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let range_check = RangeCheckChip::construct(self.range_check.clone());
+
+        let v = layouter.assign_region(
+            || "v - lo",
+            |mut region| {
+                self.q_lo.enable(&mut region, 0)?;
+                let v = region.assign_advice(|| "value", self.value, 0, || value)?;
+                let lo = self.assign_bound(&mut region, "lo", self.lo, lo)?;
+
+                let d = v.value().copied() - lo.value();
+                range_check.assign_in_region(&mut region, 0, d)?;
+                Ok(v)
+            },
+        )?;
+
+        layouter.assign_region(
+            || "hi - 1 - v",
+            |mut region| {
+                self.q_hi.enable(&mut region, 0)?;
+                let v = v.copy_advice(|| "value", &mut region, self.value, 0)?;
+                let hi = self.assign_bound(&mut region, "hi", self.hi, hi)?;
+
+                let d = hi.value().copied() - Value::known(F::one()) - v.value();
+                range_check.assign_in_region(&mut region, 0, d)
+            },
+        )?;
+
+        Ok(v)
+    }
+
+    fn assign_bound(
+        &self,
+        region: &mut Region<'_, F>,
+        name: &str,
+        column: Column<Advice>,
+        bound: Bound<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        match bound {
+            Bound::Constant(c) => region.assign_advice_from_constant(|| name, column, 0, c),
+            Bound::Instance(row) => {
+                region.assign_advice_from_instance(|| name, self.instance, row, column, 0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    use super::*;
+
+    /// Checks `lo <= value < hi`, the bounds are public when `public` is set.
+    #[derive(Default)]
+    struct MyCircuit<F: FieldExt, const NUM_BITS: usize> {
+        value: Value<F>,
+        bounds: (u64, u64),
+        public: bool,
+    }
+
+    impl<F: FieldExt, const NUM_BITS: usize> Circuit<F> for MyCircuit<F, NUM_BITS> {
+        type Config = IntervalConfig<F, NUM_BITS>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                value: Value::unknown(),
+                bounds: self.bounds,
+                public: self.public,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = meta.advice_column();
+            let instance = meta.instance_column();
+            IntervalConfig::configure(meta, value, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load_table(&mut layouter)?;
+
+            let (lo, hi) = if self.public {
+                (Bound::Instance(0), Bound::Instance(1))
+            } else {
+                let (lo, hi) = self.bounds;
+                (Bound::Constant(F::from(lo)), Bound::Constant(F::from(hi)))
+            };
+            config.assign(layouter.namespace(|| "interval"), self.value, lo, hi)?;
+
+            Ok(())
+        }
+    }
+
+    fn age(value: u64) -> MyCircuit<Fp, 8> {
+        MyCircuit {
+            value: Value::known(Fp::from(value)),
+            bounds: (18, 130),
+            public: false,
+        }
+    }
+
+    fn price(value: u64) -> MyCircuit<Fp, 32> {
+        MyCircuit {
+            value: Value::known(Fp::from(value)),
+            bounds: (0, 0),
+            public: true,
+        }
+    }
+
+    #[test]
+    fn test_interval_constant_bounds() {
+        let k = 9;
+
+        for value in [18, 65, 129] {
+            let prover = MockProver::run(k, &age(value), vec![vec![]]).unwrap();
+            prover.assert_satisfied();
+        }
+
+        // the differences below 2^8 are looked up
+        for (value, region) in [(17, (1, "v - lo")), (130, (2, "hi - 1 - v"))] {
+            let prover = MockProver::run(k, &age(value), vec![vec![]]).unwrap();
+            assert_eq!(
+                prover.verify(),
+                Err(vec![VerifyFailure::Lookup {
+                    lookup_index: 0,
+                    location: FailureLocation::InRegion {
+                        region: region.into(),
+                        offset: 0
+                    }
+                }])
+            );
+        }
+    }
+
+    #[test]
+    fn test_interval_public_bounds() {
+        let k = 9;
+        let bounds = vec![Fp::from(250), Fp::from(100_000)];
+
+        for value in [250, 4_999, 99_999] {
+            let prover = MockProver::run(k, &price(value), vec![bounds.clone()]).unwrap();
+            prover.assert_satisfied();
+        }
+        for value in [0, 249, 100_000, u32::MAX as u64] {
+            let prover = MockProver::run(k, &price(value), vec![bounds.clone()]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // the same price against other public limits
+        let prover =
+            MockProver::run(k, &price(4_999), vec![vec![Fp::from(5_000), bounds[1]]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_interval_real_proof() {
+        let k = 9;
+        let bounds = [Fp::from(250), Fp::from(100_000)];
+
+        assert!(crate::prove::prove_and_verify(k, price(4_999), &[&bounds]).is_ok());
+    }
+}